@@ -0,0 +1,16 @@
+// Generates `include/one_slot_agent.h` from `src/capi.rs`.
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("Couldn't read cbindgen.toml");
+
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(format!("{}/src/lib.rs", crate_dir))
+        .generate()
+        .expect("Couldn't generate include/one_slot_agent.h")
+        .write_to_file(format!("{}/include/one_slot_agent.h", crate_dir));
+}
@@ -0,0 +1,103 @@
+/*
+ * C interface for one_slot_agent.
+ *
+ * Every function here forwards to the same registry the Rust `SlottedAgent`
+ * builder uses, so scripts registered from C/C++ plugins are dispatched by the
+ * same hubs.
+ *
+ * Generated from `src/capi.rs` by `build.rs`, don't edit by hand.
+ */
+
+#ifndef ONE_SLOT_AGENT_H
+#define ONE_SLOT_AGENT_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Status lines use the game's `LUA_SCRIPT_STATUS_FUNC_*` values.
+ */
+#define OSA_STATUS_LINE_PRE 0
+
+#define OSA_STATUS_LINE_MAIN 1
+
+#define OSA_STATUS_LINE_END 2
+
+typedef struct OsaAgent OsaAgent;
+
+/**
+ * Decides whether an entry uses the group, from the entry's fighter.
+ */
+typedef bool (*OsaSlotSelector)(const void *ctx, void *module_accessor);
+
+/**
+ * `L2CAgentBase*` for ACMD, `L2CFighterCommon*`/`L2CWeaponCommon*` for
+ * statuses and frame functions.
+ */
+typedef void (*OsaAcmdFunction)(void *agent);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Registers into the first loaded copy of the crate instead of this one.
+ * Must be called before `osa_agent_new`.
+ */
+void osa_enable_shared_registry(void);
+
+/**
+ * `name` is the agent name, e.g. "mario" or "mario_fireball". The handle is
+ * released by `osa_install`.
+ */
+OsaAgent *osa_agent_new(const char *name);
+
+/**
+ * Colors (costume slots) the following registrations apply to.
+ */
+void osa_set_colors(OsaAgent *agent, const int32_t *colors, size_t len);
+
+/**
+ * Human readable name for the group, shown in logs and queries.
+ */
+void osa_set_label(OsaAgent *agent, const char *label);
+
+/**
+ * Selects the group with `selector(ctx, module_accessor)` instead of by
+ * color. Colors set as well still have to match. `ctx` must outlive the game.
+ */
+void osa_set_selector(OsaAgent *agent, OsaSlotSelector selector, const void *ctx);
+
+/**
+ * The category is taken from the script name prefix, e.g. "game_attackairf".
+ */
+void osa_register_acmd(OsaAgent *agent, const char *name, OsaAcmdFunction function);
+
+/**
+ * Runs `function` before the agent's original script instead of replacing it.
+ */
+void osa_register_acmd_before(OsaAgent *agent, const char *name, OsaAcmdFunction function);
+
+/**
+ * Runs `function` after the agent's original script instead of replacing it.
+ */
+void osa_register_acmd_after(OsaAgent *agent, const char *name, OsaAcmdFunction function);
+
+void osa_register_status(OsaAgent *agent, int32_t line, int32_t kind, const void *function);
+
+/**
+ * Runs every frame on the agent's main line while the slot is active.
+ */
+void osa_register_frame(OsaAgent *agent, const void *function);
+
+/**
+ * Installs the agent and frees the handle.
+ */
+void osa_install(OsaAgent *agent);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* ONE_SLOT_AGENT_H */
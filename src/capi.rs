@@ -0,0 +1,148 @@
+//! C interface for one_slot_agent.
+//!
+//! Every function here forwards to the same registry the Rust `SlottedAgent`
+//! builder uses, so scripts registered from C/C++ plugins are dispatched by the
+//! same hubs. `include/one_slot_agent.h` is generated from this file by
+//! `build.rs`.
+
+use std::ffi::{c_char, c_void, CStr};
+use smashline::*;
+
+use crate::SlottedAgent;
+use crate::selector::RawSelector;
+
+/// `L2CAgentBase*` for ACMD, `L2CFighterCommon*`/`L2CWeaponCommon*` for
+/// statuses and frame functions.
+pub type OsaAcmdFunction = unsafe extern "C" fn(agent: *mut c_void);
+
+/// Decides whether an entry uses the group, from the entry's fighter.
+pub type OsaSlotSelector = Option<unsafe extern "C" fn(ctx: *const c_void, module_accessor: *mut c_void) -> bool>;
+
+/// Status lines use the game's `LUA_SCRIPT_STATUS_FUNC_*` values.
+pub const OSA_STATUS_LINE_PRE: i32 = 0;
+pub const OSA_STATUS_LINE_MAIN: i32 = 1;
+pub const OSA_STATUS_LINE_END: i32 = 2;
+
+unsafe fn str_from_c<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+unsafe fn acmd_function(function: OsaAcmdFunction) -> AcmdFunction {
+    std::mem::transmute(function)
+}
+
+/// Registers into the first loaded copy of the crate instead of this one.
+/// Must be called before `osa_agent_new`.
+#[no_mangle]
+pub extern "C" fn osa_enable_shared_registry() {
+    crate::enable_shared_registry();
+}
+
+/// `name` is the agent name, e.g. "mario" or "mario_fireball". The handle is
+/// released by `osa_install`.
+#[no_mangle]
+pub unsafe extern "C" fn osa_agent_new(name: *const c_char) -> *mut SlottedAgent {
+    if name.is_null() {
+        log::error!("osa_agent_new: agent name is null");
+        return std::ptr::null_mut();
+    }
+    let Some(name) = str_from_c(name) else {
+        log::error!("osa_agent_new: agent name is not valid UTF-8");
+        return std::ptr::null_mut();
+    };
+    Box::into_raw(Box::new(SlottedAgent::new(name)))
+}
+
+/// Colors (costume slots) the following registrations apply to.
+#[no_mangle]
+pub unsafe extern "C" fn osa_set_colors(agent: *mut SlottedAgent, colors: *const i32, len: usize) {
+    let Some(agent) = agent.as_mut() else {
+        return;
+    };
+
+    let colors = if colors.is_null() {
+        Vec::new()
+    } else {
+        std::slice::from_raw_parts(colors, len).to_vec()
+    };
+    agent.set_color2(colors);
+}
+
+/// Human readable name for the group, shown in logs and queries.
+#[no_mangle]
+pub unsafe extern "C" fn osa_set_label(agent: *mut SlottedAgent, label: *const c_char) {
+    let (Some(agent), Some(label)) = (agent.as_mut(), str_from_c(label)) else {
+        return;
+    };
+    agent.label(label);
+}
+
+/// Selects the group with `selector(ctx, module_accessor)` instead of by
+/// color. Colors set as well still have to match. `ctx` must outlive the game.
+#[no_mangle]
+pub unsafe extern "C" fn osa_set_selector(agent: *mut SlottedAgent, selector: OsaSlotSelector, ctx: *const c_void) {
+    let Some(agent) = agent.as_mut() else {
+        return;
+    };
+    agent.selector_raw(RawSelector {
+        ctx,
+        matches: std::mem::transmute(selector),
+    });
+}
+
+/// The category is taken from the script name prefix, e.g. "game_attackairf".
+#[no_mangle]
+pub unsafe extern "C" fn osa_register_acmd(agent: *mut SlottedAgent, name: *const c_char, function: OsaAcmdFunction) {
+    let (Some(agent), Some(name)) = (agent.as_mut(), str_from_c(name)) else {
+        return;
+    };
+    agent.acmd(name, acmd_function(function), Priority::Default);
+}
+
+/// Runs `function` before the agent's original script instead of replacing it.
+#[no_mangle]
+pub unsafe extern "C" fn osa_register_acmd_before(agent: *mut SlottedAgent, name: *const c_char, function: OsaAcmdFunction) {
+    let (Some(agent), Some(name)) = (agent.as_mut(), str_from_c(name)) else {
+        return;
+    };
+    agent.acmd_before(name, acmd_function(function), Priority::Default);
+}
+
+/// Runs `function` after the agent's original script instead of replacing it.
+#[no_mangle]
+pub unsafe extern "C" fn osa_register_acmd_after(agent: *mut SlottedAgent, name: *const c_char, function: OsaAcmdFunction) {
+    let (Some(agent), Some(name)) = (agent.as_mut(), str_from_c(name)) else {
+        return;
+    };
+    agent.acmd_after(name, acmd_function(function), Priority::Default);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn osa_register_status(agent: *mut SlottedAgent, line: i32, kind: i32, function: *const ()) {
+    let Some(agent) = agent.as_mut() else {
+        return;
+    };
+    agent.status_raw(line, kind, function);
+}
+
+/// Runs every frame on the agent's main line while the slot is active.
+#[no_mangle]
+pub unsafe extern "C" fn osa_register_frame(agent: *mut SlottedAgent, function: *const ()) {
+    let Some(agent) = agent.as_mut() else {
+        return;
+    };
+    agent.frame_raw(function);
+}
+
+/// Installs the agent and frees the handle.
+#[no_mangle]
+pub unsafe extern "C" fn osa_install(agent: *mut SlottedAgent) {
+    if agent.is_null() {
+        return;
+    }
+    let mut agent = Box::from_raw(agent);
+    agent.install();
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use crate::{SLOTTED_AGENTS, SLOT_GROUPS};
+
+pub const SLOT_CONFIG_PATH: &str = "sd:/ultimate/one_slot_agent/slots.txt";
+
+// Callbacks registered with `on_reload`, run before the slots are reloaded.
+static mut RELOAD_CALLBACKS: Vec<extern "C" fn()> = Vec::new();
+
+pub(crate) unsafe fn add_reload_callback(f: extern "C" fn()) {
+    RELOAD_CALLBACKS.push(f);
+}
+
+pub(crate) unsafe fn run_reload_callbacks() {
+    for f in RELOAD_CALLBACKS.iter() {
+        f();
+    }
+}
+
+// Sets the colors of labeled groups from `SLOT_CONFIG_PATH`, if it exists, on
+// top of the colors they were registered with, one group per line:
+//
+//     <label> <color> [<color>...]
+//
+// Blank lines and lines starting with `#` are skipped.
+pub(crate) fn apply_slot_config() {
+    if !std::path::Path::new(SLOT_CONFIG_PATH).exists() {
+        return;
+    }
+
+    let contents = match std::fs::read_to_string(SLOT_CONFIG_PATH) {
+        Ok(contents) => contents,
+        Err(e) => {
+            log::warn!("Couldn't load {}: {}", SLOT_CONFIG_PATH, e);
+            return;
+        }
+    };
+
+    let mut colors_by_group = HashMap::new();
+    let slot_groups = SLOT_GROUPS.read();
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let label = fields.next().unwrap_or_default();
+        let Ok(colors) = fields.map(str::parse::<i32>).collect::<Result<Vec<i32>, _>>() else {
+            log::warn!("{}:{}: couldn't parse `{}`, skipping...", SLOT_CONFIG_PATH, i + 1, line);
+            continue;
+        };
+
+        let groups: Vec<usize> = slot_groups
+            .iter()
+            .enumerate()
+            .filter(|(_, g)| g.copy_of.is_none() && g.label.as_deref() == Some(label))
+            .map(|(id, _)| id)
+            .collect();
+
+        if groups.is_empty() {
+            log::warn!("{}:{}: no group is labeled \"{}\", skipping...", SLOT_CONFIG_PATH, i + 1, label);
+        }
+        for id in groups {
+            colors_by_group.insert(id, colors.clone());
+        }
+    }
+
+    // Kirby's copy groups follow the group they copy.
+    let copies: Vec<(usize, Vec<i32>)> = slot_groups
+        .iter()
+        .enumerate()
+        .filter_map(|(id, g)| {
+            let (group, _, _) = g.copy_of?;
+            Some((id, colors_by_group.get(&group)?.clone()))
+        })
+        .collect();
+    colors_by_group.extend(copies);
+    drop(slot_groups);
+
+    let mut slotted_agents = SLOTTED_AGENTS.write();
+    for info in slotted_agents.values_mut().flatten() {
+        if let Some(colors) = colors_by_group.get(&info.group) {
+            info.color = colors.clone();
+        }
+    }
+
+    log::info!("Loaded colors of {} slot groups from {}", colors_by_group.len(), SLOT_CONFIG_PATH);
+}
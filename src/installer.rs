@@ -1,47 +1,104 @@
+use std::collections::{HashMap, HashSet};
+use once_cell::sync::Lazy;
 use smashline::*;
 use smash::lib::lua_const::*;
 use smash::app::{lua_bind::*, *};
 use smash::hash40;
 
-use crate::{SLOTTED_AGENTS, ACMD_BASE_NAME, StatusScript, UUID};
+use crate::{SLOTTED_AGENTS, ACMD_BASE_NAME, AcmdPlacement, SlottedInfo, StatusScript, UUID};
+use crate::logging::{self, agent_log};
+use crate::trace;
 
 type OpffFunction = unsafe extern "C" fn(&mut L2CFighterCommon);
 type OnStartFunction = unsafe extern "C" fn(&mut L2CFighterCommon);
 
-static mut INITIALIZED: [bool; 8] = [false; 8];
-static mut SLOTTED_INFO_INDEX: [Option<usize>; 8] = [None; 8];
-static mut OPFF: [Option<OpffFunction>; 8] = [None; 8];
+// Tracked per agent since some entries are made of several fighters, like
+// Popo and Nana or Pyra and Mythra, which each need their own scripts
+// installed the first time they run.
+static mut INITIALIZED: Lazy<[HashSet<u64>; 8]> = Lazy::new(Default::default);
+pub(crate) static mut SLOTTED_GROUP: [Option<usize>; 8] = [None; 8];
+static mut SLOT_RESOLVED: [bool; 8] = [false; 8];
+// Set once `set_active_slot` picked the entry's group.
+static mut SLOT_SWITCHED: [bool; 8] = [false; 8];
+// Groups fighters of an entry resolved on their own because the entry's group
+// isn't registered on them, e.g. Mythra registered with other colors than
+// Pyra, by fighter hash.
+static mut AGENT_GROUP: Lazy<[HashMap<u64, usize>; 8]> = Lazy::new(Default::default);
+static mut OPFF: Lazy<[HashMap<u64, OpffFunction>; 8]> = Lazy::new(Default::default);
+
+// Changes every time an entry's slot is resolved, so per-slot state created
+// for a previous match or a previous slot is never handed out again.
+static mut SLOT_GENERATION: [u64; 8] = [0; 8];
+static mut NEXT_GENERATION: u64 = 1;
+
+// Kind and color of every entry seen this match, for the slot report.
+static mut ENTRY_KIND_COLOR: [Option<(i32, i32)>; 8] = [None; 8];
+// (copied fighter kind, copied color) Kirby's copy group was resolved for.
+static mut KIRBY_COPY: [(i32, i32); 8] = [(-1, -1); 8];
+pub(crate) static mut KIRBY_COPY_GROUP: [Option<usize>; 8] = [None; 8];
+
+// Status functions the fighters of an entry had before a group replaced them,
+// keyed by (agent hash, line, kind), so they can be put back when the entry
+// switches to another slot. `None` for statuses the fighter didn't have, which
+// are left to the group since there's nothing to put back.
+static mut ORIGINAL_STATUSES: Lazy<[HashMap<(u64, i32, i32), Option<*const ()>>; 8]> = Lazy::new(Default::default);
+
+// Scripts each fighter of an entry has a hub installed for, so they can be
+// given back their original scripts when the entry switches to another slot.
+static mut HUBBED_ACMDS: Lazy<[HashMap<u64, HashSet<u64>>; 8]> = Lazy::new(Default::default);
+
+// Set once fighters run their first frame. Every fighter's `on_start` comes
+// before that, so variant rolls are only cleared by the first one of a match.
+static mut MATCH_STARTED: bool = true;
 
 static mut COLOR_BOOL_CONVERTED: bool = false;
 
 pub unsafe extern "C" fn on_start(fighter: &mut L2CFighterCommon) {
-    INITIALIZED.fill(false);
-    SLOTTED_INFO_INDEX.fill(None);
-    OPFF.fill(None);
-
-    if !COLOR_BOOL_CONVERTED {
-        let mut slotted_agents = SLOTTED_AGENTS.write();
-
-        if let Some(slotted_info) = slotted_agents.get_mut(&fighter.agent_kind_hash.hash) {
-            for info in slotted_info.iter_mut() {
-                if let Some(c) = info.color_bool {
-                    if info.color.is_empty() {
-                        info.color = (*c).iter()
-                            .enumerate()
-                            .filter_map(|(i, &v)| if v { Some(i as i32) } else { None })
-                            .collect();
-                        info.color_bool = None;
-                    }
-                }
-            }
+    if sv_information::is_ready_go() {
+        // Started mid-match, e.g. a Pokemon being swapped in. The entry keeps
+        // the slot its team already resolved, this fighter just installs its
+        // own scripts again on its next frame.
+        let entry_id = WorkModule::get_int(fighter.module_accessor, *FIGHTER_INSTANCE_WORK_ID_INT_ENTRY_ID);
+        INITIALIZED[entry_id as usize].remove(&fighter.agent_kind_hash.hash);
+        OPFF[entry_id as usize].remove(&fighter.agent_kind_hash.hash);
+        ORIGINAL_STATUSES[entry_id as usize].retain(|&(hash, _, _), _| hash != fighter.agent_kind_hash.hash);
+        HUBBED_ACMDS[entry_id as usize].remove(&fighter.agent_kind_hash.hash);
+    } else {
+        INITIALIZED.iter_mut().for_each(HashSet::clear);
+        SLOTTED_GROUP.fill(None);
+        SLOT_RESOLVED.fill(false);
+        SLOT_SWITCHED.fill(false);
+        AGENT_GROUP.iter_mut().for_each(HashMap::clear);
+        OPFF.iter_mut().for_each(HashMap::clear);
+        ENTRY_KIND_COLOR.fill(None);
+        KIRBY_COPY.fill((-1, -1));
+        KIRBY_COPY_GROUP.fill(None);
+        ORIGINAL_STATUSES.iter_mut().for_each(HashMap::clear);
+        HUBBED_ACMDS.iter_mut().for_each(HashMap::clear);
+
+        if std::mem::take(&mut MATCH_STARTED) {
+            crate::selector::clear_variant_rolls();
         }
+    }
 
+    if !COLOR_BOOL_CONVERTED {
+        update_colors();
         COLOR_BOOL_CONVERTED = true;
+
+        crate::registry::validate_weapon_owners();
     }
 
     let slotted_agents = SLOTTED_AGENTS.read();
 
     if let Some(slotted_info) = slotted_agents.get(&fighter.agent_kind_hash.hash) {
+        let entry_id = WorkModule::get_int(fighter.module_accessor, *FIGHTER_INSTANCE_WORK_ID_INT_ENTRY_ID);
+        let color = WorkModule::get_int(fighter.module_accessor, *FIGHTER_INSTANCE_WORK_ID_INT_COLOR);
+        if (0..8).contains(&entry_id) {
+            crate::selector::roll_variants(entry_id as usize, slotted_info, |info| {
+                colors_match(fighter.module_accessor, info, color)
+            });
+        }
+
         for info in slotted_info.iter() {
             if let Some(on_start) = info.on_start {
                 let f: OnStartFunction = std::mem::transmute(on_start);
@@ -51,37 +108,453 @@ pub unsafe extern "C" fn on_start(fighter: &mut L2CFighterCommon) {
     }
 }
 
+// Turns `set_color` tables into colors, then applies the slot config on top.
+// Tables are kept so they're read again on reload, unless colors were given
+// explicitly as well, in which case those are used. Starts over from the
+// registered colors, so lines removed from the config are undone on reload.
+unsafe fn update_colors() {
+    let mut slotted_agents = SLOTTED_AGENTS.write();
+
+    for info in slotted_agents.values_mut().flatten() {
+        info.color = info.registered_color.clone();
+
+        if let Some(c) = info.color_bool {
+            if !COLOR_BOOL_CONVERTED && !info.color.is_empty() {
+                info.color_bool = None;
+                continue;
+            }
+
+            info.color = (*c).iter()
+                .enumerate()
+                .filter_map(|(i, &v)| if v { Some(i as i32) } else { None })
+                .collect();
+        }
+    }
+    drop(slotted_agents);
+
+    crate::config::apply_slot_config();
+}
+
+static mut RELOAD_REQUESTED: bool = false;
+
+// Reloads on the next frame of a slotted fighter, where no lock on the slots
+// is held. Hubs, frame functions and status scripts run while one is, so
+// reloading right away from them would deadlock.
+pub(crate) unsafe fn request_reload() {
+    RELOAD_REQUESTED = true;
+}
+
+// Reads the slot configuration again and makes every entry resolve its slot
+// again on its next frame, without leaving the match. Resolving undoes the
+// hubs and statuses of the slot each fighter had, and entries keep that slot
+// until then.
+unsafe fn reload_slots() {
+    crate::config::run_reload_callbacks();
+    update_colors();
+
+    INITIALIZED.iter_mut().for_each(HashSet::clear);
+    SLOT_RESOLVED.fill(false);
+    SLOT_SWITCHED.fill(false);
+    AGENT_GROUP.iter_mut().for_each(HashMap::clear);
+    KIRBY_COPY.fill((-1, -1));
+    KIRBY_COPY_GROUP.fill(None);
+
+    log::info!("Reloaded slots");
+}
+
+// Shield + special + a taunt, in training mode only.
+unsafe fn training_combo(boma: *mut BattleObjectModuleAccessor, taunt: i32) -> bool {
+    smashball::is_training_mode()
+        && ControlModule::check_button_on(boma, *CONTROL_PAD_BUTTON_GUARD)
+        && ControlModule::check_button_on(boma, *CONTROL_PAD_BUTTON_SPECIAL)
+        && ControlModule::check_button_trigger(boma, taunt)
+}
+
+static mut DEBUG_DISPLAY: Option<extern "C" fn(*const u8, usize)> = None;
+
+pub(crate) unsafe fn set_debug_display(f: extern "C" fn(*const u8, usize)) {
+    DEBUG_DISPLAY = Some(f);
+}
+
+// Number of entries in the current match, 0 outside of one.
+unsafe fn entry_count() -> usize {
+    let fighter_manager = singletons::FighterManager();
+    if fighter_manager.is_null() {
+        return 0;
+    }
+
+    (FighterManager::entry_count(fighter_manager).max(0) as usize).min(8)
+}
+
+// What each entry resolved to, one line per fighter of the entry that has
+// installed its slot. Entries none of whose fighters are slotted never run
+// `opff`, so only their color is known.
+pub(crate) unsafe fn slot_report() -> String {
+    use std::fmt::Write;
+
+    let slotted_agents = SLOTTED_AGENTS.read();
+    let slot_groups = crate::SLOT_GROUPS.read();
+    let mut report = String::new();
+
+    for entry_id in 0..entry_count() {
+        let Some((kind, color)) = ENTRY_KIND_COLOR[entry_id] else {
+            let information = FighterManager::get_fighter_information(
+                singletons::FighterManager(),
+                FighterEntryID(entry_id as i32),
+            );
+            let _ = writeln!(
+                report,
+                "P{} c{:02}: not slotted",
+                entry_id + 1,
+                FighterInformation::fighter_color(information)
+            );
+            continue;
+        };
+
+        let fighter = crate::util::get_fighter_name(kind).unwrap_or("unknown");
+        let group = match SLOTTED_GROUP[entry_id] {
+            Some(group) => match slot_groups.get(group).and_then(|g| g.label.as_ref()) {
+                Some(label) => format!("group {} \"{}\"", group, label),
+                None => format!("group {}", group),
+            },
+            None => "no group".to_string(),
+        };
+        let _ = writeln!(report, "P{} {} c{:02}: {}", entry_id + 1, fighter, color, group);
+
+        for &hash in INITIALIZED[entry_id].iter() {
+            let Some(slotted_info) = slotted_agents.get(&hash) else {
+                continue;
+            };
+            let agent = crate::util::get_fighter_id_from_hash(hash)
+                .and_then(crate::util::get_fighter_name)
+                .unwrap_or(fighter);
+
+            for info in group_infos(slotted_info, entry_id as i32, hash) {
+                let statuses: Vec<String> = info.statuses
+                    .iter()
+                    .map(|s| format!("{}:{}", s.kind, s.line))
+                    .collect();
+
+                let _ = writeln!(
+                    report,
+                    "  {}{}: {} ACMD, statuses [{}]{}",
+                    agent,
+                    if info.copy_of.is_some() { " (copy)" } else { "" },
+                    info.acmds.len(),
+                    statuses.join(", "),
+                    if OPFF[entry_id].contains_key(&hash) { ", frame" } else { "" }
+                );
+            }
+        }
+    }
+
+    if report.is_empty() {
+        report.push_str("No entries this match\n");
+    }
+    report
+}
+
+unsafe fn show_slot_report() {
+    let report = slot_report();
+    log::info!("Slot report:\n{}", report);
+
+    // The system's error dialog is the one text display the game always has.
+    // It pauses the game until it's closed.
+    match DEBUG_DISPLAY {
+        Some(f) => f(report.as_ptr(), report.len()),
+        None => skyline::error::show_error(0, "One Slot Agent slot report", &report),
+    }
+}
+
 pub unsafe extern "C" fn opff(fighter: &mut L2CFighterCommon) {
     let entry_id = WorkModule::get_int(fighter.module_accessor, *FIGHTER_INSTANCE_WORK_ID_INT_ENTRY_ID);
     let color = WorkModule::get_int(fighter.module_accessor, *FIGHTER_INSTANCE_WORK_ID_INT_COLOR);
+    MATCH_STARTED = true;
 
-    if !INITIALIZED[entry_id as usize] {
+    if training_combo(fighter.module_accessor, *CONTROL_PAD_BUTTON_APPEAL_HI) {
+        RELOAD_REQUESTED = true;
+    }
+    if std::mem::take(&mut RELOAD_REQUESTED) {
+        reload_slots();
+    }
+    if training_combo(fighter.module_accessor, *CONTROL_PAD_BUTTON_APPEAL_LW) {
+        show_slot_report();
+    }
+
+    let kind = utility::get_kind(&mut *fighter.module_accessor);
+    ENTRY_KIND_COLOR[entry_id as usize] = Some((kind, color));
+
+    if kind == *FIGHTER_KIND_KIRBY {
+        update_kirby_copy(fighter, entry_id, color);
+    }
+
+    let hash = fighter.agent_kind_hash.hash;
+
+    if !INITIALIZED[entry_id as usize].contains(&hash) {
         let slotted_agents = SLOTTED_AGENTS.read();
 
-        if let Some(slotted_info) = slotted_agents.get(&fighter.agent_kind_hash.hash) {
-            for (i, info) in slotted_info.iter().enumerate() {
-                if info.color.contains(&color) {
-                    SLOTTED_INFO_INDEX[entry_id as usize] = Some(i);
+        if let Some(slotted_info) = slotted_agents.get(&hash) {
+            if SLOT_RESOLVED[entry_id as usize] {
+                // Another fighter of this entry already resolved its slot, or
+                // the slot was switched since this fighter last installed it.
+                let infos = match group_info(slotted_info, entry_id, hash) {
+                    Some(info) if !SLOT_SWITCHED[entry_id as usize] => {
+                        let matched = matching_groups(fighter.module_accessor, slotted_info, color);
+                        if matched.last().map(|last| last.group) == Some(info.group) {
+                            matched
+                        } else {
+                            vec![info]
+                        }
+                    }
+                    Some(info) => vec![info],
+                    None if SLOT_SWITCHED[entry_id as usize] => vec![],
+                    None => {
+                        // This fighter's groups were registered apart from the
+                        // one the entry resolved to, so it picks its own.
+                        let matched = matching_groups(fighter.module_accessor, slotted_info, color);
+                        if let Some(info) = matched.last() {
+                            agent_log!(info, logging::agent_name(hash), "Entry {} with color {} resolved to group {} for this fighter", entry_id, color, info.group);
+                            AGENT_GROUP[entry_id as usize].insert(hash, info.group);
+                        }
+                        matched
+                    }
+                };
+
+                if infos.is_empty() {
+                    uninstall_group(fighter, entry_id);
+                } else {
+                    install_group(fighter, entry_id, &infos);
+                }
+            } else {
+                let matched = matching_groups(fighter.module_accessor, slotted_info, color);
 
-                    install_slotted_acmds(fighter);
-                    install_slotted_statuses(fighter, &info.statuses);
+                SLOTTED_GROUP[entry_id as usize] = matched.last().map(|info| info.group);
+                SLOT_GENERATION[entry_id as usize] = NEXT_GENERATION;
+                NEXT_GENERATION += 1;
+
+                match matched.last() {
+                    Some(info) => {
+                        agent_log!(info, logging::agent_name(hash), "Entry {} with color {} resolved to group {}", entry_id, color, info.group);
+                        install_group(fighter, entry_id, &matched);
+                    }
+                    None => {
+                        agent_log!(debug, logging::agent_name(hash), "Entry {} with color {} has no group", entry_id, color);
 
-                    if let Some(opff) = info.frame {
-                        let f: OpffFunction = std::mem::transmute(opff);
-                        OPFF[entry_id as usize] = Some(f);
+                        // Only matters after a reload, when this fighter may
+                        // still have the scripts and statuses of the slot it
+                        // had before.
+                        uninstall_group(fighter, entry_id);
                     }
                 }
+                SLOT_RESOLVED[entry_id as usize] = true;
             }
         }
 
-        INITIALIZED[entry_id as usize] = true;
+        INITIALIZED[entry_id as usize].insert(hash);
     }
 
-    if let Some(f) = OPFF[entry_id as usize] {
+    if let Some(f) = OPFF[entry_id as usize].get(&hash) {
         f(fighter);
     }
 }
 
+unsafe fn colors_match(boma: *mut BattleObjectModuleAccessor, info: &SlottedInfo, color: i32) -> bool {
+    if let Some(selector) = &info.selector {
+        selector.matches(boma) && (info.color.is_empty() || info.color.contains(&color))
+    } else {
+        info.color.contains(&color)
+    }
+}
+
+// Variants also have to be the one the entry rolled for their pool.
+unsafe fn group_matches(boma: *mut BattleObjectModuleAccessor, info: &SlottedInfo, color: i32) -> bool {
+    if !colors_match(boma, info, color) {
+        return false;
+    }
+
+    let slot_groups = crate::SLOT_GROUPS.read();
+    match slot_groups.get(info.group).and_then(|g| g.variant.as_ref()) {
+        Some((pool, _)) => {
+            let entry_id = WorkModule::get_int(boma, *FIGHTER_INSTANCE_WORK_ID_INT_ENTRY_ID);
+            crate::selector::rolled_variant(entry_id as usize, pool) == Some(info.group)
+        }
+        None => true,
+    }
+}
+
+// Every group of the fighter that matches `color`, in registration order. The
+// last one is the entry's slot, for its hubs and frame function, while the
+// statuses of all of them are installed.
+unsafe fn matching_groups<'a>(boma: *mut BattleObjectModuleAccessor, slotted_info: &'a [SlottedInfo], color: i32) -> Vec<&'a SlottedInfo> {
+    let matched: Vec<&SlottedInfo> = slotted_info
+        .iter()
+        .filter(|info| info.copy_of.is_none() && group_matches(boma, info, color))
+        .collect();
+
+    if let [.., last] = matched.as_slice() {
+        if matched.len() > 1 {
+            let groups: Vec<usize> = matched.iter().map(|info| info.group).collect();
+            agent_log!(
+                warn,
+                logging::agent_name(fighter_hash(boma)),
+                "Color {} matches groups {:?}, group {} is used and the statuses of all of them are installed",
+                color,
+                groups,
+                last.group
+            );
+        }
+    }
+    matched
+}
+
+unsafe fn install_group(fighter: &mut L2CFighterCommon, entry_id: i32, infos: &[&SlottedInfo]) {
+    let hash = fighter.agent_kind_hash.hash;
+    let statuses: Vec<&StatusScript> = infos.iter().flat_map(|info| &info.statuses).collect();
+
+    restore_acmds(fighter, entry_id);
+    install_slotted_acmds(fighter);
+    restore_statuses(fighter, entry_id, &statuses);
+
+    for s in &statuses {
+        ORIGINAL_STATUSES[entry_id as usize]
+            .entry((hash, s.line, s.kind))
+            .or_insert_with(|| {
+                let original = fighter.sv_get_status_func(&s.kind.into(), &s.line.into()).get_ptr() as *const ();
+                (!original.is_null()).then_some(original)
+            });
+    }
+    for info in infos {
+        install_slotted_statuses(fighter, &info.statuses);
+    }
+
+    if let Some(opff) = infos.iter().rev().find_map(|info| info.frame) {
+        let f: OpffFunction = std::mem::transmute(opff);
+        OPFF[entry_id as usize].insert(hash, f);
+    } else {
+        OPFF[entry_id as usize].remove(&hash);
+    }
+}
+
+unsafe fn uninstall_group(fighter: &mut L2CFighterCommon, entry_id: i32) {
+    restore_acmds(fighter, entry_id);
+    restore_statuses(fighter, entry_id, &[]);
+    OPFF[entry_id as usize].remove(&fighter.agent_kind_hash.hash);
+}
+
+// Gives `agent` back the original scripts of every hub installed on it. Only
+// fighters are tracked, weapons install their group again on every spawn.
+unsafe fn restore_acmds(agent: &mut L2CAgentBase, entry_id: i32) {
+    let hash = agent.agent_kind_hash.hash;
+    let Some(hubbed) = HUBBED_ACMDS[entry_id as usize].remove(&hash) else {
+        return;
+    };
+
+    for script_hash in hubbed {
+        if let Some(original) = original_acmd(hash, script_hash) {
+            agent.sv_set_function_hash(std::mem::transmute(original as *const ()), Hash40::new_raw(script_hash));
+        }
+    }
+}
+
+// Puts back the statuses a previous group replaced on `fighter` that
+// `statuses` doesn't replace as well.
+unsafe fn restore_statuses(fighter: &mut L2CFighterCommon, entry_id: i32, statuses: &[&StatusScript]) {
+    let hash = fighter.agent_kind_hash.hash;
+
+    ORIGINAL_STATUSES[entry_id as usize].retain(|&(agent_hash, line, kind), function| {
+        if agent_hash != hash || statuses.iter().any(|s| s.line == line && s.kind == kind) {
+            return true;
+        }
+
+        if let Some(function) = *function {
+            fighter.sv_set_status_func(
+                kind.into(),
+                line.into(),
+                &mut *(function as *mut skyline::libc::c_void)
+            );
+        }
+        false
+    });
+}
+
+pub(crate) unsafe fn set_active_slot(fighter: &mut L2CFighterCommon, group: Option<usize>) -> bool {
+    let entry_id = WorkModule::get_int(fighter.module_accessor, *FIGHTER_INSTANCE_WORK_ID_INT_ENTRY_ID);
+    if !(0..8).contains(&entry_id) {
+        return false;
+    }
+
+    let hash = fighter.agent_kind_hash.hash;
+    let slotted_agents = SLOTTED_AGENTS.read();
+    let info = group.and_then(|group| {
+        slotted_agents
+            .get(&hash)?
+            .iter()
+            .find(|info| info.group == group && info.copy_of.is_none())
+    });
+
+    if let (Some(group), None) = (group, info) {
+        agent_log!(warn, logging::agent_name(hash), "Slot group {} has nothing registered for this fighter, not switching to it", group);
+        return false;
+    }
+
+    agent_log!(info, logging::agent_name(hash), "Entry {} switched to group {:?}", entry_id, group);
+
+    SLOTTED_GROUP[entry_id as usize] = group;
+    SLOT_RESOLVED[entry_id as usize] = true;
+    SLOT_SWITCHED[entry_id as usize] = true;
+    AGENT_GROUP[entry_id as usize].clear();
+    SLOT_GENERATION[entry_id as usize] = NEXT_GENERATION;
+    NEXT_GENERATION += 1;
+
+    match info {
+        Some(info) => install_group(fighter, entry_id, &[info]),
+        None => uninstall_group(fighter, entry_id),
+    }
+
+    // The entry's other fighters switch on their next frame.
+    INITIALIZED[entry_id as usize].retain(|&h| h == hash);
+    true
+}
+
+unsafe fn update_kirby_copy(fighter: &mut L2CFighterCommon, entry_id: i32, color: i32) {
+    let copy_chara = WorkModule::get_int(fighter.module_accessor, *FIGHTER_KIRBY_INSTANCE_WORK_ID_INT_COPY_CHARA);
+    // Color of the fighter Kirby actually copied, which is what the hat uses.
+    let copied_color = WorkModule::get_int(fighter.module_accessor, *FIGHTER_KIRBY_INSTANCE_WORK_ID_INT_COPY_COLOR);
+
+    if (copy_chara, copied_color) == KIRBY_COPY[entry_id as usize] {
+        return;
+    }
+    KIRBY_COPY[entry_id as usize] = (copy_chara, copied_color);
+    KIRBY_COPY_GROUP[entry_id as usize] = None;
+
+    let slotted_agents = SLOTTED_AGENTS.read();
+
+    if let Some(slotted_info) = slotted_agents.get(&fighter.agent_kind_hash.hash) {
+        for info in slotted_info.iter() {
+            let Some((fighter_id, mode)) = info.copy_of else {
+                continue;
+            };
+
+            let color = if mode == crate::KirbyCopy::KirbyColor as i32 {
+                color
+            } else {
+                copied_color
+            };
+
+            if fighter_id == copy_chara && info.color.contains(&color) {
+                KIRBY_COPY_GROUP[entry_id as usize] = Some(info.group);
+                break;
+            }
+        }
+    }
+    drop(slotted_agents);
+
+    // Drops the hubs of the previous copy ability along with Kirby's own,
+    // which are installed again right away.
+    restore_acmds(fighter, entry_id);
+    install_slotted_acmds(fighter);
+}
+
 pub unsafe extern "C" fn weapon_opff(weapon: &mut L2CFighterCommon) {
     let owner_id = WorkModule::get_int(weapon.module_accessor, *WEAPON_INSTANCE_WORK_ID_INT_LINK_OWNER);
     let owner_boma = sv_battle_object::module_accessor(owner_id as u32);
@@ -90,9 +563,7 @@ pub unsafe extern "C" fn weapon_opff(weapon: &mut L2CFighterCommon) {
     let slotted_agents = SLOTTED_AGENTS.read();
 
     if let Some(slotted_info) = slotted_agents.get(&weapon.agent_kind_hash.hash) {
-        if let Some(info_index) = SLOTTED_INFO_INDEX[owner_entry_id as usize] {
-            let info = &slotted_info[info_index];
-
+        if let Some(info) = group_info(slotted_info, owner_entry_id, fighter_hash(owner_boma)) {
             if let Some(opff) = info.frame {
                 let f: OpffFunction = std::mem::transmute(opff);
                 f(weapon);
@@ -101,6 +572,66 @@ pub unsafe extern "C" fn weapon_opff(weapon: &mut L2CFighterCommon) {
     }
 }
 
+// Hash of the fighter `boma` belongs to.
+unsafe fn fighter_hash(boma: *mut BattleObjectModuleAccessor) -> u64 {
+    crate::util::get_fighter_hash(utility::get_kind(&mut *boma)).unwrap_or_default()
+}
+
+// The group `entry_id` uses for the fighter `fighter_hash` (and its weapons).
+unsafe fn entry_group(entry_id: i32, fighter_hash: u64) -> Option<usize> {
+    AGENT_GROUP[entry_id as usize]
+        .get(&fighter_hash)
+        .copied()
+        .or(SLOTTED_GROUP[entry_id as usize])
+}
+
+// The group resolved for `entry_id`'s fighter `fighter_hash`, as registered on
+// the agent `slotted_info` belongs to.
+pub(crate) unsafe fn group_info(slotted_info: &[SlottedInfo], entry_id: i32, fighter_hash: u64) -> Option<&SlottedInfo> {
+    let group = entry_group(entry_id, fighter_hash)?;
+    slotted_info.iter().find(|info| info.group == group)
+}
+
+pub(crate) struct ResolvedSlot {
+    pub entry_id: usize,
+    pub group: usize,
+    pub generation: u64,
+    pub inherited: bool,
+}
+
+// Every group active for `entry_id` on the agent `slotted_info` belongs to, the
+// ones that take priority first.
+pub(crate) unsafe fn group_infos(slotted_info: &[SlottedInfo], entry_id: i32, fighter_hash: u64) -> impl Iterator<Item = &SlottedInfo> {
+    [KIRBY_COPY_GROUP[entry_id as usize], entry_group(entry_id, fighter_hash)]
+        .into_iter()
+        .flatten()
+        .filter_map(move |group| slotted_info.iter().find(|info| info.group == group))
+}
+
+pub(crate) unsafe fn query_slot(boma: *mut BattleObjectModuleAccessor) -> Option<ResolvedSlot> {
+    let category = utility::get_category(&mut *boma);
+    let (boma, inherited) = if category == *BATTLE_OBJECT_CATEGORY_FIGHTER {
+        (boma, false)
+    } else if category == *BATTLE_OBJECT_CATEGORY_WEAPON {
+        let owner_id = WorkModule::get_int(boma, *WEAPON_INSTANCE_WORK_ID_INT_LINK_OWNER);
+        (sv_battle_object::module_accessor(owner_id as u32), true)
+    } else {
+        return None;
+    };
+
+    let entry_id = WorkModule::get_int(boma, *FIGHTER_INSTANCE_WORK_ID_INT_ENTRY_ID);
+    if !(0..8).contains(&entry_id) {
+        return None;
+    }
+
+    entry_group(entry_id, fighter_hash(boma)).map(|group| ResolvedSlot {
+        entry_id: entry_id as usize,
+        group,
+        generation: SLOT_GENERATION[entry_id as usize],
+        inherited,
+    })
+}
+
 unsafe fn install_slotted_acmds(agent: &mut L2CFighterBase) {
     let category = utility::get_category(&mut *agent.module_accessor);
     let uuid: String = unsafe { UUID.iter().collect() };
@@ -120,12 +651,14 @@ unsafe fn install_slotted_acmds(agent: &mut L2CFighterBase) {
 unsafe fn install_slotted_statuses(agent: &mut L2CFighterBase, statuses: &[StatusScript]) -> bool {
     let mut restore_original = true;
     for s in statuses {
-        if s.kind == 0 && s.line == StatusLine::Pre {
+        agent_log!(debug, logging::agent_name(agent.agent_kind_hash.hash), "Overriding status {}:{}", s.kind, s.line);
+
+        if s.kind == 0 && s.line == StatusLine::Pre as i32 {
             restore_original = false;
         }
         agent.sv_set_status_func(
             s.kind.into(),
-            (s.line as i32).into(),
+            s.line.into(),
             &mut *(s.function as *mut skyline::libc::c_void)
         );
     }
@@ -148,16 +681,25 @@ macro_rules! create_acmd_installers {
                     let slotted_agents = SLOTTED_AGENTS.read();
 
                     if let Some(slotted_info) = slotted_agents.get(&agent.agent_kind_hash.hash) {
-                        if let Some(info_index) = SLOTTED_INFO_INDEX[entry_id as usize] {
-                            let info = &slotted_info[info_index];
+                        for info in group_infos(slotted_info, entry_id, fighter_hash(boma)) {
                             let acmds = &info.acmds;
 
                             for (hash, script) in acmds {
                                 if script.category as i32 == Acmd::[<$category:camel>] as i32 {
+                                    let hub = [<$category _hub>] as *const ();
+                                    save_original_acmd(agent, *hash, hub);
+
                                     agent.sv_set_function_hash(
-                                        std::mem::transmute([<$category _hub>] as *const ()),
+                                        std::mem::transmute(hub),
                                         Hash40::new_raw(*hash)
                                     );
+
+                                    if category == *BATTLE_OBJECT_CATEGORY_FIGHTER {
+                                        HUBBED_ACMDS[entry_id as usize]
+                                            .entry(agent.agent_kind_hash.hash)
+                                            .or_default()
+                                            .insert(*hash);
+                                    }
                                 }
                             }
                         }
@@ -170,11 +712,43 @@ macro_rules! create_acmd_installers {
 
 create_acmd_installers!(game, effect, sound, expression);
 
+type RawAcmdFunction = unsafe extern "C" fn(&mut L2CAgentBase, &mut Variadic) -> u64;
+
+// Script each agent kind had under a hash before a hub replaced it, keyed by
+// (agent hash, script hash). Every instance of a kind starts out with the same
+// scripts, so the first one captured is kept.
+static mut ORIGINAL_ACMDS: Lazy<HashMap<(u64, u64), RawAcmdFunction>> = Lazy::new(HashMap::new);
+
+unsafe fn save_original_acmd(agent: &mut L2CAgentBase, hash: u64, hub: *const ()) {
+    let key = (agent.agent_kind_hash.hash, hash);
+    if ORIGINAL_ACMDS.contains_key(&key) {
+        return;
+    }
+
+    let original = agent.sv_get_function_hash(Hash40::new_raw(hash)) as *const ();
+    if !original.is_null() && original != hub {
+        ORIGINAL_ACMDS.insert(key, std::mem::transmute(original));
+    }
+}
+
+pub(crate) unsafe fn original_acmd(agent_hash: u64, hash: u64) -> Option<RawAcmdFunction> {
+    ORIGINAL_ACMDS.get(&(agent_hash, hash)).copied()
+}
+
+// Reported once per script, since hubs run every frame of the move.
+unsafe fn warn_uncaptured(agent_hash: u64, script_hash: u64, message: std::fmt::Arguments) {
+    static mut REPORTED: Lazy<HashSet<(u64, u64)>> = Lazy::new(HashSet::new);
+
+    if REPORTED.insert((agent_hash, script_hash)) {
+        agent_log!(warn, logging::agent_name(agent_hash), "{}", message);
+    }
+}
+
 macro_rules! create_acmd_hubs {
     ($($category:ident),*) => {
         paste::paste! {
             $(
-                unsafe extern "C" fn [<$category _hub>](agent: &mut L2CAgentBase, _variadic: &mut Variadic) -> u64 {
+                unsafe extern "C" fn [<$category _hub>](agent: &mut L2CAgentBase, variadic: &mut Variadic) -> u64 {
                     let category = utility::get_category(&mut *agent.module_accessor);
                     let boma = if category == *BATTLE_OBJECT_CATEGORY_FIGHTER {
                         agent.module_accessor
@@ -183,26 +757,75 @@ macro_rules! create_acmd_hubs {
                         sv_battle_object::module_accessor(owner_id as u32)
                     };
                     let entry_id = WorkModule::get_int(boma, *FIGHTER_INSTANCE_WORK_ID_INT_ENTRY_ID);
+                    let agent_hash = agent.agent_kind_hash.hash;
                     let slotted_agents = SLOTTED_AGENTS.read();
 
-                    if let Some(slotted_info) = slotted_agents.get(&agent.agent_kind_hash.hash) {
-                        if let Some(info_index) = SLOTTED_INFO_INDEX[entry_id as usize] {
-                            let info = &slotted_info[info_index];
-                            let acmds = &info.acmds;
-
-                            let motion_kind = MotionModule::motion_kind(agent.module_accessor);
-                            let game_hash = MotionModule::animcmd_name_hash(agent.module_accessor, Hash40::new_raw(motion_kind));
-
-                            if let Some(base_name) = ACMD_BASE_NAME.read().get(&game_hash) {
-                                let script_name = stringify!([<$category>]).to_string() + "_" + base_name;
-                                if let Some(script) = acmds.get(&hash40(&script_name)) {
-                                    (script.function)(agent);
+                    let motion_kind = MotionModule::motion_kind(agent.module_accessor);
+                    let game_hash = MotionModule::animcmd_name_hash(agent.module_accessor, Hash40::new_raw(motion_kind));
+                    let acmd_base_name = ACMD_BASE_NAME.read();
+                    let base_name = acmd_base_name.get(&game_hash);
+                    let mut ret = 0;
+
+                    let outcome = match (slotted_agents.get(&agent_hash), base_name) {
+                        (None, _) => trace::Outcome::NotSlotted,
+                        (Some(_), None) => trace::Outcome::UnknownMotion,
+                        (Some(slotted_info), Some(base_name)) => {
+                            let script_name = stringify!([<$category>]).to_string() + "_" + base_name;
+                            let script_hash = hash40(&script_name);
+
+                            let script = group_infos(slotted_info, entry_id, fighter_hash(boma))
+                                .find_map(|info| info.acmds.get(&script_hash).map(|script| (info.group, *script)));
+
+                            if let Some((group, script)) = script {
+                                agent_log!(trace, logging::agent_name(agent_hash), "Entry {} runs slotted {}", entry_id, script_name);
+                                let original = match script.placement {
+                                    AcmdPlacement::Replace => None,
+                                    _ => {
+                                        let original = original_acmd(agent_hash, script_hash);
+                                        if original.is_none() {
+                                            warn_uncaptured(agent_hash, script_hash, format_args!("Original {} wasn't captured, only the slotted one runs", script_name));
+                                        }
+                                        original
+                                    }
+                                };
+
+                                if script.placement == AcmdPlacement::After {
+                                    if let Some(original) = original {
+                                        ret = original(agent, variadic);
+                                    }
+                                }
+                                (script.function)(agent);
+                                if script.placement == AcmdPlacement::Before {
+                                    if let Some(original) = original {
+                                        ret = original(agent, variadic);
+                                    }
                                 }
+                                trace::Outcome::Hit(group)
+                            } else if let Some(original) = original_acmd(agent_hash, script_hash) {
+                                // Partial movesets keep the scripts they don't replace.
+                                agent_log!(trace, logging::agent_name(agent_hash), "Entry {} has no slotted {}, running the original", entry_id, script_name);
+                                ret = original(agent, variadic);
+                                trace::Outcome::Fallback
+                            } else {
+                                warn_uncaptured(agent_hash, script_hash, format_args!("Entry {} has no slotted {} and its original wasn't captured", entry_id, script_name));
+                                trace::Outcome::Miss
                             }
                         }
+                    };
+
+                    if trace::enabled() {
+                        trace::record(trace::Dispatch {
+                            entry_id,
+                            agent_hash,
+                            category: stringify!([<$category>]),
+                            motion_kind,
+                            game_hash,
+                            base_name: base_name.cloned(),
+                            outcome,
+                        });
                     }
 
-                    0
+                    ret
                 }
             )*
         }
@@ -215,6 +838,16 @@ pub unsafe extern "C" fn weapon_installer_helper(weapon: &mut L2CWeaponCommon) -
     let owner_id = WorkModule::get_int(weapon.module_accessor, *WEAPON_INSTANCE_WORK_ID_INT_LINK_OWNER);
     let owner_boma = sv_battle_object::module_accessor(owner_id as u32);
     let owner_entry_id = WorkModule::get_int(owner_boma, *FIGHTER_INSTANCE_WORK_ID_INT_ENTRY_ID);
+    let owner_hash = fighter_hash(owner_boma);
+
+    agent_log!(
+        trace,
+        logging::agent_name(weapon.agent_kind_hash.hash),
+        "Owner is object {} of entry {}, using group {:?}",
+        owner_id,
+        owner_entry_id,
+        entry_group(owner_entry_id, owner_hash)
+    );
 
     let slotted_agents = SLOTTED_AGENTS.read();
 
@@ -223,8 +856,7 @@ pub unsafe extern "C" fn weapon_installer_helper(weapon: &mut L2CWeaponCommon) -
     let mut restore_original = true;
 
     if let Some(slotted_info) = slotted_agents.get(&weapon.agent_kind_hash.hash) {
-        if let Some(info_index) = SLOTTED_INFO_INDEX[owner_entry_id as usize] {
-            let info = &slotted_info[info_index];
+        if let Some(info) = group_info(slotted_info, owner_entry_id, owner_hash) {
             if !install_slotted_statuses(weapon, &info.statuses) {
                 restore_original = false;
             }
@@ -4,41 +4,105 @@
     unused
 )]
 
-mod util;
+pub mod util;
 mod installer;
+mod registry;
+mod capi;
+mod slot_state;
+mod config;
+mod trace;
+pub mod selector;
+pub mod logging;
 
 use std::collections::{HashMap, HashSet};
 use once_cell::sync::Lazy;
 use smashline::{*, locks::RwLock};
 use smash::hash40;
 
+use registry::RawGroup;
+use selector::{RawSelector, SlotSelector};
+use logging::agent_log;
+
+pub use slot_state::with_slot_state;
+pub use config::SLOT_CONFIG_PATH;
+pub use util::{register_agent_kind, AgentCategory};
+
 const UUID_LEN: usize = 8;
 pub(crate) static mut UUID: [char; UUID_LEN] = ['\0'; UUID_LEN];
 
+// Where a slotted script runs relative to the agent's original one.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub(crate) enum AcmdPlacement {
+    Replace,
+    Before,
+    After,
+}
+
+#[derive(Clone, Copy)]
 pub(crate) struct AcmdScript {
     category: Acmd,
     function: AcmdFunction,
+    placement: AcmdPlacement,
 }
 
 pub(crate) struct StatusScript {
-    line: StatusLine,
+    line: i32,
     kind: i32,
     function: *const (),
 }
 
 struct SlottedInfo {
+    group: usize,
     color: Vec<i32>,
+    // Colors as registered, which `color` is rebuilt from whenever the slot
+    // config is applied again.
+    registered_color: Vec<i32>,
     color_bool: Option<*const [bool; 256]>,
+    selector: Option<RawSelector>,
     frame: Option<*const ()>,
     on_start: Option<*const ()>,
     acmds: HashMap<u64, AcmdScript>,
     statuses: Vec<StatusScript>,
+    // Set on Kirby's groups holding a copy ability: (copied fighter kind, `KirbyCopy`).
+    copy_of: Option<(i32, i32)>,
+    // Filled from the partner's registrations, until the agent registers
+    // something for the group itself.
+    mirrored: bool,
 }
 
 pub(crate) static SLOTTED_AGENTS: Lazy<RwLock<HashMap<u64, Vec<SlottedInfo>>>> = Lazy::new(|| RwLock::new(HashMap::new()));
-static mut INSTALLED_AGENTS: Lazy<HashSet<u64>> = Lazy::new(HashSet::new);
+pub(crate) static mut INSTALLED_AGENTS: Lazy<HashSet<u64>> = Lazy::new(HashSet::new);
+pub(crate) struct SlotGroup {
+    color: Vec<i32>,
+    color_bool: Option<*const [bool; 256]>,
+    selector: Option<RawSelector>,
+    label: Option<String>,
+    // (pool, weight) for groups that are one of the variants of a pool.
+    variant: Option<(String, u32)>,
+    // (group, copied fighter kind, `KirbyCopy`) for Kirby's copy ability groups.
+    copy_of: Option<(usize, i32, i32)>,
+}
+
+pub(crate) static SLOT_GROUPS: Lazy<RwLock<Vec<SlotGroup>>> = Lazy::new(|| RwLock::new(Vec::new()));
 pub(crate) static ACMD_BASE_NAME: Lazy<RwLock<HashMap<u64, String>>> = Lazy::new(|| RwLock::new(HashMap::new()));
 
+// Colors, table, selector, label and variant of a group.
+type GroupSettings = (Vec<i32>, Option<*const [bool; 256]>, Option<RawSelector>, Option<String>, Option<(String, u32)>);
+
+// Groups each fighter was registered with in this plugin. A weapon with no
+// group of its own uses its owner's, if the owner has exactly one.
+static mut OWNER_GROUPS: Lazy<HashMap<i32, Vec<GroupSettings>>> = Lazy::new(HashMap::new);
+
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KirbyCopy {
+    /// Kirby uses the group when the fighter it copied is using it.
+    CopiedColor,
+    /// Kirby uses the group when Kirby's own color is part of it.
+    KirbyColor,
+}
+
 pub struct SlottedAgent {
     agent: Agent,
     name: String,
@@ -47,6 +111,15 @@ pub struct SlottedAgent {
     is_cloned: bool,
     color: Vec<i32>,
     color_bool: Option<*const [bool; 256]>,
+    selector: Option<RawSelector>,
+    label: Option<String>,
+    variant: Option<(String, u32)>,
+    // The owner's group, for weapons that don't set one themselves.
+    owner_group: Option<GroupSettings>,
+    owner_warned: bool,
+    weapons: Vec<String>,
+    with_team: bool,
+    kirby_copy: Option<KirbyCopy>,
 }
 
 impl SlottedAgent {
@@ -82,6 +155,7 @@ impl SlottedAgent {
             is_weapon = true;
             hash40(&("weapon_kind_".to_owned() + agent))
         } else {
+            agent_log!(error, agent, "Unknown agent `{}`, register it with `util::register_agent_kind` first", agent);
             hash40("invalid")
         };
         Self {
@@ -92,9 +166,133 @@ impl SlottedAgent {
             is_cloned: false,
             color: Vec::new(),
             color_bool: None,
+            selector: None,
+            label: None,
+            variant: None,
+            owner_group: None,
+            owner_warned: false,
+            weapons: Vec::new(),
+            with_team: false,
+            kirby_copy: None,
         }
     }
 
+    pub fn new_group(agent: &str, label: &str) -> Self {
+        let mut slotted_agent = Self::new(agent);
+        slotted_agent.label(label);
+        slotted_agent
+    }
+
+    pub fn label(&mut self, label: &str) -> &mut Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Gives the listed articles of this fighter the same group, e.g.
+    /// `with_weapons(&["fireball"])` on `mario` for `mario_fireball`.
+    pub fn with_weapons(&mut self, weapons: &[&str]) -> &mut Self {
+        let prefix = self.name.clone() + "_";
+
+        for weapon in weapons {
+            let weapon = if weapon.starts_with(&prefix) {
+                weapon.to_string()
+            } else {
+                prefix.clone() + weapon
+            };
+
+            if util::get_weapon_id(&weapon) == -1 {
+                agent_log!(warn, &self.name, "Couldn't find weapon `{}` for {}", weapon, self.display_name());
+            } else if !self.weapons.contains(&weapon) {
+                self.weapons.push(weapon);
+            }
+        }
+        self
+    }
+
+    pub fn all_weapons(&mut self) -> &mut Self {
+        let fighter_id = util::get_fighter_id(&self.name);
+
+        for (weapon, _) in util::get_fighter_weapons(fighter_id) {
+            if !self.weapons.iter().any(|w| w == weapon) {
+                self.weapons.push(weapon.to_string());
+            }
+        }
+        self
+    }
+
+    /// Gives every fighter of this fighter's team the same group, e.g. the
+    /// trainer and all three Pokemon for any of `ptrainer`, `pzenigame`,
+    /// `pfushigisou` or `plizardon`, or both of Pyra and Mythra for `eflame`
+    /// or `elight`.
+    pub fn with_team(&mut self) -> &mut Self {
+        if util::get_team(&self.name).is_empty() {
+            agent_log!(warn, &self.name, "{} isn't part of a team", self.display_name());
+        } else {
+            self.with_team = true;
+        }
+        self
+    }
+
+    /// Also gives Kirby this group's neutral special ACMD (`*_specialn*` and
+    /// `*_specialairn*`) while Kirby has this fighter's copy ability. Only ACMD
+    /// is copied: Kirby runs copy abilities under its own status kinds, so the
+    /// group's status scripts don't apply to it.
+    pub fn kirby_copy(&mut self, mode: KirbyCopy) -> &mut Self {
+        if self.is_weapon {
+            agent_log!(warn, &self.name, "Kirby can't copy weapon {}", self.display_name());
+        } else {
+            self.kirby_copy = Some(mode);
+        }
+        self
+    }
+
+    /// Selects the group with `selector` instead of by color. If colors are
+    /// set as well, they still have to match.
+    pub fn selector<S: SlotSelector>(&mut self, selector: S) -> &mut Self {
+        self.selector = Some(RawSelector::new(selector));
+        self
+    }
+
+    /// Selects the group by the entry's name tag, see `selector::NameTag`. Only
+    /// matches once a plugin sets `selector::set_name_tag_source`.
+    pub fn name_tag(&mut self, name_tag: selector::NameTag) -> &mut Self {
+        if !selector::has_name_tag_source() {
+            agent_log!(
+                warn,
+                &self.name,
+                "{} is selected by name tag but no name tag source is set yet, see `selector::set_name_tag_source`",
+                self.display_name()
+            );
+        }
+        self.selector(name_tag)
+    }
+
+    /// Makes the group one of the variants of `pool`. At the start of every
+    /// match each entry rolls one of the pool's groups whose colors (and
+    /// selector) match it, weighted by `weight`, e.g. `variant("mario_alts", 3)`
+    /// and `variant("mario_alts", 1)` for a 3 to 1 split. The rolled variant is
+    /// on `SlotHandle::variant`.
+    pub fn variant(&mut self, pool: &str, weight: u32) -> &mut Self {
+        self.variant = Some((pool.to_string(), weight));
+        self
+    }
+
+    pub(crate) fn selector_raw(&mut self, selector: RawSelector) -> &mut Self {
+        self.selector = selector.get();
+        self
+    }
+
+    /// Selects the group by the Mii's chosen specials (1 to 3, `None` for any)
+    /// instead of by color. Colors set as well still have to match.
+    pub fn mii_specials(&mut self, n: Option<i32>, s: Option<i32>, hi: Option<i32>, lw: Option<i32>) -> &mut Self {
+        if !["miifighter", "miiswordsman", "miigunner"].contains(&self.name.as_str()) {
+            agent_log!(warn, &self.name, "{} isn't a Mii fighter, ignoring its specials", self.display_name());
+            return self;
+        }
+
+        self.selector(selector::MiiSpecials { n, s, hi, lw })
+    }
+
     pub fn set_color(&mut self, color: &[bool; 256]) -> &mut Self {
         self.color_bool = Some(color as *const [bool; 256]);
         self
@@ -117,71 +315,195 @@ impl SlottedAgent {
         self
     }
 
+    /// Replaces `name` for entries using this group. Scripts that aren't
+    /// registered keep playing the agent's original ones.
     pub fn acmd(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
-        if name.starts_with("game") {
-            self.game_acmd(name, function, priority);
-        } else if name.starts_with("effect") {
-            self.effect_acmd(name, function, priority);
-        } else if name.starts_with("sound") {
-            self.sound_acmd(name, function, priority);
-        } else if name.starts_with("expression") {
-            self.expression_acmd(name, function, priority);
-        } else {
-            println!("ACMD Category for {} could not be found! Skipping...", name);
-        }
+        self.placed_acmd(name, function, AcmdPlacement::Replace);
+        self
+    }
+
+    /// Runs `function` and then the agent's original `name` for entries using
+    /// this group. The original only starts once `function` returns, so waits
+    /// in `function` should stay before the original's first frame.
+    pub fn acmd_before(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
+        self.placed_acmd(name, function, AcmdPlacement::Before);
+        self
+    }
+
+    /// Runs the agent's original `name` and then `function` for entries using
+    /// this group, e.g. to add a hitbox to the vanilla script. Frames waited
+    /// for in `function` are the motion's, so ones the original already
+    /// waited past run right away.
+    pub fn acmd_after(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
+        self.placed_acmd(name, function, AcmdPlacement::After);
         self
     }
 
     pub fn game_acmd(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
-        self.acmd_helper(name, AcmdScript { category: Acmd::Game, function, });
+        self.acmd_helper(name, Acmd::Game, function, AcmdPlacement::Replace);
+        self
+    }
+
+    pub fn game_acmd_before(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
+        self.acmd_helper(name, Acmd::Game, function, AcmdPlacement::Before);
+        self
+    }
+
+    pub fn game_acmd_after(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
+        self.acmd_helper(name, Acmd::Game, function, AcmdPlacement::After);
         self
     }
 
     pub fn effect_acmd(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
-        self.acmd_helper(name, AcmdScript { category: Acmd::Effect, function, });
+        self.acmd_helper(name, Acmd::Effect, function, AcmdPlacement::Replace);
+        self
+    }
+
+    pub fn effect_acmd_before(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
+        self.acmd_helper(name, Acmd::Effect, function, AcmdPlacement::Before);
+        self
+    }
+
+    pub fn effect_acmd_after(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
+        self.acmd_helper(name, Acmd::Effect, function, AcmdPlacement::After);
         self
     }
 
     pub fn sound_acmd(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
-        self.acmd_helper(name, AcmdScript { category: Acmd::Sound, function, });
+        self.acmd_helper(name, Acmd::Sound, function, AcmdPlacement::Replace);
+        self
+    }
+
+    pub fn sound_acmd_before(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
+        self.acmd_helper(name, Acmd::Sound, function, AcmdPlacement::Before);
+        self
+    }
+
+    pub fn sound_acmd_after(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
+        self.acmd_helper(name, Acmd::Sound, function, AcmdPlacement::After);
         self
     }
 
     pub fn expression_acmd(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
-        self.acmd_helper(name, AcmdScript { category: Acmd::Expression, function, });
+        self.acmd_helper(name, Acmd::Expression, function, AcmdPlacement::Replace);
+        self
+    }
+
+    pub fn expression_acmd_before(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
+        self.acmd_helper(name, Acmd::Expression, function, AcmdPlacement::Before);
+        self
+    }
+
+    pub fn expression_acmd_after(&mut self, name: &str, function: AcmdFunction, priority: Priority) -> &mut Self {
+        self.acmd_helper(name, Acmd::Expression, function, AcmdPlacement::After);
         self
     }
 
-    fn acmd_helper(&mut self, name: &str, script: AcmdScript) {
-        let mut slotted_agents = SLOTTED_AGENTS.write();
-        let slotted_info = slotted_agents.entry(self.hash).or_default();
+    fn placed_acmd(&mut self, name: &str, function: AcmdFunction, placement: AcmdPlacement) {
+        if name.starts_with("game") {
+            self.acmd_helper(name, Acmd::Game, function, placement);
+        } else if name.starts_with("effect") {
+            self.acmd_helper(name, Acmd::Effect, function, placement);
+        } else if name.starts_with("sound") {
+            self.acmd_helper(name, Acmd::Sound, function, placement);
+        } else if name.starts_with("expression") {
+            self.acmd_helper(name, Acmd::Expression, function, placement);
+        } else {
+            agent_log!(warn, &self.name, "ACMD Category for {} could not be found! Skipping...", name);
+        }
+    }
+
+    fn acmd_helper(&mut self, name: &str, category: Acmd, function: AcmdFunction, placement: AcmdPlacement) {
+        let group = self.raw_group();
+        unsafe {
+            (registry::get().register_acmd)(&group, name.as_ptr(), name.len(), category as i32, placement as i32, function);
+        }
+    }
 
-        let hash = hash40(name);
-        if let Some(info) = slotted_info.iter_mut().find(|info| self.color == info.color) {
-            info.acmds.insert(hash, script);
+    fn raw_group(&mut self) -> RawGroup {
+        if self.is_weapon {
+            self.inherit_owner_group();
         } else {
-            slotted_info.push({
-                let mut acmds: HashMap<u64, AcmdScript> = HashMap::new();
-                acmds.insert(hash, script);
-                SlottedInfo {
-                    color: self.color.clone(),
-                    color_bool: self.color_bool,
-                    frame: None,
-                    on_start: None,
-                    acmds,
-                    statuses: vec![]
+            let fighter_id = util::get_fighter_id(&self.name);
+            if fighter_id != -1 {
+                let settings = (self.color.clone(), self.color_bool, self.selector, self.label.clone(), self.variant.clone());
+                let groups = unsafe { OWNER_GROUPS.entry(fighter_id).or_default() };
+
+                if !groups.iter().any(|g| g.0 == settings.0 && g.1 == settings.1 && g.2 == settings.2 && g.4 == settings.4) {
+                    groups.push(settings);
                 }
-            })
+            }
         }
 
-        if let Some((_, base_name)) = name.split_once('_') {
-            let game = "game_".to_owned() + base_name;
-            ACMD_BASE_NAME
-                .write()
-                .entry(hash40(&game))
-                .or_insert_with(|| {
-                    base_name.to_string()
-                });
+        let (color, color_bool, selector, label, variant) = match &self.owner_group {
+            Some((color, color_bool, selector, label, variant)) => (color, *color_bool, *selector, self.label.as_deref().or(label.as_deref()), variant),
+            None => (&self.color, self.color_bool, self.selector, self.label.as_deref(), &self.variant),
+        };
+
+        let mut group = RawGroup::new(self.hash, color, color_bool, label);
+        if let Some(selector) = selector {
+            group.selector = selector;
+        }
+        if let Some((pool, weight)) = variant {
+            group.variant_pool = pool.as_ptr();
+            group.variant_pool_len = pool.len();
+            group.variant_weight = *weight;
+        }
+        group
+    }
+
+    fn inherit_owner_group(&mut self) {
+        self.owner_group = None;
+        if !self.color.is_empty() || self.color_bool.is_some() || self.selector.is_some() || self.variant.is_some() {
+            return;
+        }
+
+        let Some(groups) = util::get_weapon_id_from_hash(self.hash)
+            .and_then(util::get_weapon_owner_id)
+            .and_then(|owner_id| unsafe { OWNER_GROUPS.get(&owner_id) })
+        else {
+            return;
+        };
+
+        match groups.as_slice() {
+            [group] => self.owner_group = Some(group.clone()),
+            _ if !self.owner_warned => {
+                agent_log!(
+                    warn,
+                    &self.name,
+                    "{} has no colors and its owner has {} groups, set its colors to pick one",
+                    self.display_name(),
+                    groups.len()
+                );
+                self.owner_warned = true;
+            }
+            _ => {}
+        }
+    }
+
+    fn copy_group_to(&self, other: &mut SlottedAgent) {
+        match &self.owner_group {
+            Some((color, color_bool, selector, label, variant)) => {
+                other.color = color.clone();
+                other.color_bool = *color_bool;
+                other.selector = *selector;
+                other.label = self.label.clone().or(label.clone());
+                other.variant = variant.clone();
+            }
+            None => {
+                other.color = self.color.clone();
+                other.color_bool = self.color_bool;
+                other.selector = self.selector;
+                other.label = self.label.clone();
+                other.variant = self.variant.clone();
+            }
+        }
+    }
+
+    fn display_name(&self) -> String {
+        match &self.label {
+            Some(label) => format!("`{}` ({})", self.name, label),
+            None => format!("`{}`", self.name),
         }
     }
 
@@ -192,35 +514,20 @@ impl SlottedAgent {
         kind: i32,
         function: M::Function<T>,
     ) -> &mut Self {
+        self.status_raw(M::LINE as i32, kind, unsafe { M::cast_function(function) });
+        self
+    }
+
+    pub(crate) fn status_raw(&mut self, line: i32, kind: i32, function: *const ()) {
         if self.hash == hash40("invalid") {
-            println!("Couldn't install slotted status for `{}`", self.name);
-            return self;
+            agent_log!(error, &self.name, "Couldn't install slotted status for {}", self.display_name());
+            return;
         }
 
-        let status_script = StatusScript {
-            line: M::LINE,
-            kind,
-            function: unsafe { M::cast_function(function) }
-        };
-
-        let mut slotted_agents = SLOTTED_AGENTS.write();
-        let slotted_info = slotted_agents.entry(self.hash).or_default();
-
-        if let Some(info) = slotted_info.iter_mut().find(|info| self.color == info.color) {
-            info.statuses.push(status_script);
-        } else {
-            slotted_info.push({
-                SlottedInfo {
-                    color: self.color.clone(),
-                    color_bool: self.color_bool,
-                    frame: None,
-                    on_start: None,
-                    acmds: HashMap::new(),
-                    statuses: vec![status_script]
-                }
-            })
+        let group = self.raw_group();
+        unsafe {
+            (registry::get().register_status)(&group, line, kind, function);
         }
-        self
     }
 
     #[allow(unused)]
@@ -240,31 +547,20 @@ impl SlottedAgent {
         line: M,
         function: M::LineFunction<T>,
     ) -> &mut Self {
+        self.frame_raw(unsafe { M::cast_line_function(function) });
+        self
+    }
+
+    pub(crate) fn frame_raw(&mut self, function: *const ()) {
         if self.hash == hash40("invalid") {
-            println!("Couldn't install on_line for `{}`", self.name);
-            return self;
+            agent_log!(error, &self.name, "Couldn't install on_line for {}", self.display_name());
+            return;
         }
 
-        let frame = Some(unsafe { M::cast_line_function(function) });
-
-        let mut slotted_agents = SLOTTED_AGENTS.write();
-        let slotted_info = slotted_agents.entry(self.hash).or_default();
-
-        if let Some(info) = slotted_info.iter_mut().find(|info| self.color == info.color) {
-            info.frame = frame;
-        } else {
-            slotted_info.push({
-                SlottedInfo {
-                    color: self.color.clone(),
-                    color_bool: self.color_bool,
-                    frame,
-                    on_start: None,
-                    acmds: HashMap::new(),
-                    statuses: vec![]
-                }
-            })
+        let group = self.raw_group();
+        unsafe {
+            (registry::get().register_frame)(&group, function);
         }
-        self
     }
 
     pub fn on_init<T>(&mut self, func: StateFunction<T>) -> &mut Self {
@@ -279,28 +575,13 @@ impl SlottedAgent {
 
     pub fn on_start<T>(&mut self, func: StateFunction<T>) -> &mut Self {
         if self.hash == hash40("invalid") {
-            println!("Couldn't install on_start for `{}`", self.name);
+            agent_log!(error, &self.name, "Couldn't install on_start for {}", self.display_name());
             return self;
         }
 
-        let f = Some(func as *const ());
-
-        let mut slotted_agents = SLOTTED_AGENTS.write();
-        let slotted_info = slotted_agents.entry(self.hash).or_default();
-
-        if let Some(info) = slotted_info.iter_mut().find(|info| self.color == info.color) {
-            info.on_start = f;
-        } else {
-            slotted_info.push({
-                SlottedInfo {
-                    color: self.color.clone(),
-                    color_bool: self.color_bool,
-                    frame: None,
-                    on_start: f,
-                    acmds: HashMap::new(),
-                    statuses: vec![]
-                }
-            })
+        let group = self.raw_group();
+        unsafe {
+            (registry::get().register_on_start)(&group, func as *const ());
         }
         self
     }
@@ -311,74 +592,183 @@ impl SlottedAgent {
     }
 
     pub fn install(&mut self) {
-        unsafe {
-            if INSTALLED_AGENTS.contains(&self.hash) {
-                return;
+        let registry = registry::get();
+        let group = self.raw_group();
+
+        for weapon in std::mem::take(&mut self.weapons) {
+            let mut slotted_weapon = SlottedAgent::new(&weapon);
+            self.copy_group_to(&mut slotted_weapon);
+
+            let weapon_group = slotted_weapon.raw_group();
+            unsafe {
+                (registry.register_group)(&weapon_group);
             }
+            slotted_weapon.install();
         }
 
-        if self.is_weapon {
-            let f = if self.is_cloned {
-                installer::slotted_cloned_weapon_installer_pre
-            } else {
-                installer::slotted_weapon_installer_pre
-            };
-            self.agent.status(Pre, 0, f);
-
-            if let Some(slotted_info) = SLOTTED_AGENTS.read().get(&self.hash) {
-                if let Some(info) = slotted_info.iter().find(|info| self.color == info.color) {
-                    if self.is_cloned {
-                        info.statuses
-                            .iter()
-                            .filter(|status| status.kind != 0)
-                            .for_each(|status| {
-                                self.agent.status(Pre, status.kind, f);
-                            });
-                    }
-
-                    if info.frame.is_some() {
-                        self.agent.on_line(Main, installer::weapon_opff);
-                    }
+        if std::mem::take(&mut self.with_team) {
+            for member in util::get_team(&self.name).iter().filter(|&&member| member != self.name) {
+                let mut slotted_member = SlottedAgent::new(member);
+                self.copy_group_to(&mut slotted_member);
+
+                let member_group = slotted_member.raw_group();
+                unsafe {
+                    (registry.register_group)(&member_group);
                 }
+                slotted_member.install();
             }
-        } else {
-            self.agent.on_start(installer::on_start);
-            self.agent.on_line(Main, installer::opff);
         }
 
-        let uuid: String = unsafe { UUID.iter().collect() };
-        self.agent.acmd(&format!("game_acmd_installer{}", uuid), installer::game_acmd_installer, Priority::Default);
-        self.agent.acmd(&format!("effect_acmd_installer{}", uuid), installer::effect_acmd_installer, Priority::Default);
-        self.agent.acmd(&format!("sound_acmd_installer{}", uuid), installer::sound_acmd_installer, Priority::Default);
-        self.agent.acmd(&format!("expression_acmd_installer{}", uuid), installer::expression_acmd_installer, Priority::Default);
-
-        self.agent.install();
+        if let Some(mode) = self.kirby_copy {
+            unsafe {
+                (registry.register_kirby_copy)(&group, mode as i32);
+            }
+        }
 
         unsafe {
-            INSTALLED_AGENTS.insert(self.hash);
+            if registry::is_local(registry) {
+                if !registry::install_slotted(&mut self.agent, &group, self.is_weapon, self.is_cloned) {
+                    return;
+                }
+            } else {
+                (registry.install)(&group, self.name.as_ptr(), self.name.len(), self.is_weapon, self.is_cloned);
+            }
         }
+
+        self.agent.install();
     }
 }
 
-pub fn get_colors(agent: &mut L2CAgentBase) -> Vec<i32> {
-    use smash::lib::lua_const::*;
-    use smash::app::{lua_bind::*, *};
+/// Makes every copy of this crate loaded by different plugins register into
+/// the copy that was loaded first, so a single set of installers and hubs
+/// serves all of them. Must be called before any `SlottedAgent` is created,
+/// later calls are ignored.
+pub fn enable_shared_registry() {
+    registry::enable_shared();
+}
 
-    let category = utility::get_category(unsafe { &mut *agent.module_accessor });
-    let boma = if category == *BATTLE_OBJECT_CATEGORY_FIGHTER {
-        agent.module_accessor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotHandle {
+    /// Id of the matched group, shared by every agent registered with the same colors.
+    pub group: usize,
+    pub label: Option<String>,
+    /// `true` if the battle object is a weapon using its owner's slot.
+    pub inherited: bool,
+    /// Index of the rolled variant in its pool, in the order the pool's groups
+    /// were registered, if the group is one.
+    pub variant: Option<usize>,
+}
+
+pub fn slot_of(boma: *mut smash::app::BattleObjectModuleAccessor) -> Option<SlotHandle> {
+    let raw = registry::query(boma)?;
+    let label = if raw.label.is_null() {
+        None
     } else {
-        let owner_id = unsafe { WorkModule::get_int(agent.module_accessor, *WEAPON_INSTANCE_WORK_ID_INT_LINK_OWNER) };
-        unsafe { sv_battle_object::module_accessor(owner_id as u32) }
+        Some(unsafe { registry::str_from_raw(raw.label, raw.label_len) }.to_string())
     };
-    let color = unsafe { WorkModule::get_int(boma, *FIGHTER_INSTANCE_WORK_ID_INT_COLOR) };
 
-    if let Some(slotted_info) = SLOTTED_AGENTS.read().get(&agent.agent_kind_hash.hash) {
-        for (i, info) in slotted_info.iter().enumerate() {
-            if info.color.contains(&color) {
-                return info.color.clone();
-            }
-        }
+    Some(SlotHandle {
+        group: raw.group,
+        label,
+        inherited: raw.inherited,
+        variant: raw.has_variant.then_some(raw.variant),
+    })
+}
+
+/// Id of the group registered with `label`, for `set_active_slot`.
+pub fn find_group(label: &str) -> Option<usize> {
+    let mut group = 0;
+
+    if unsafe { (registry::get().find_group)(label.as_ptr(), label.len(), &mut group) } {
+        Some(group)
+    } else {
+        None
+    }
+}
+
+/// Switches `fighter`'s entry to `group` (`None` for none) for the rest of the
+/// match, e.g. for a transformation. `fighter` gets the group's ACMD, statuses
+/// and frame function right away, statuses the previous slot replaced are put
+/// back, the entry's other fighters follow on their next frame and so do
+/// weapons spawned afterwards.
+/// Returns `false` if `fighter` has nothing registered for `group`.
+pub fn set_active_slot(fighter: &mut L2CFighterCommon, group: Option<usize>) -> bool {
+    unsafe { (registry::get().set_active_slot)(fighter, group.unwrap_or_default(), group.is_some()) }
+}
+
+/// Runs `f` whenever the slots are reloaded, before they're read again, e.g. to
+/// refill the tables passed to `set_color` from a config file.
+pub fn on_reload(f: extern "C" fn()) {
+    unsafe {
+        (registry::get().register_on_reload)(f);
+    }
+}
+
+/// Reads the slot configuration (`set_color` tables and `SLOT_CONFIG_PATH`)
+/// again and makes every entry resolve its slot again. Happens on the next
+/// frame of a slotted fighter, so it's safe to call from scripts. In training
+/// mode, shield + special + up taunt does the same.
+///
+/// Shield + special + down taunt shows `slot_report` on screen in training
+/// mode, as any slotted fighter.
+pub fn reload_slots() {
+    unsafe {
+        (registry::get().reload)();
+    }
+}
+
+/// Lists what every entry of the current match resolved to: its color, the
+/// matched group, and for each of its fighters how many ACMD scripts the group
+/// has, which statuses (`kind:line`) it replaces and whether it has a frame
+/// function. Entries without any slotted fighter are listed as not slotted.
+pub fn slot_report() -> String {
+    unsafe extern "C" fn write(ctx: *mut std::ffi::c_void, report: *const u8, len: usize) {
+        *(ctx as *mut String) = registry::str_from_raw(report, len).to_string();
+    }
+
+    let mut report = String::new();
+    unsafe {
+        (registry::get().slot_report)(&mut report as *mut String as *mut std::ffi::c_void, write);
+    }
+    report
+}
+
+/// Hands the report shown by the training mode combo to `f` instead, e.g. to
+/// draw it with a UI library. By default it's shown in a system dialog, which
+/// pauses the game until it's closed.
+pub fn set_debug_display(f: extern "C" fn(*const u8, usize)) {
+    unsafe {
+        (registry::get().set_debug_display)(f);
+    }
+}
+
+/// Starts or stops recording what the ACMD hubs do with each script they're
+/// called for: the motion, the script it resolved to, and whether a slotted
+/// script ran. Only the last 256 are kept. Clears what was recorded so far.
+pub fn set_dispatch_trace(enabled: bool) {
+    unsafe {
+        (registry::get().set_dispatch_trace)(enabled);
+    }
+}
+
+/// What was recorded since `set_dispatch_trace(true)`, oldest first.
+pub fn dump_dispatch_trace() -> String {
+    unsafe extern "C" fn write(ctx: *mut std::ffi::c_void, dump: *const u8, len: usize) {
+        *(ctx as *mut String) = registry::str_from_raw(dump, len).to_string();
+    }
+
+    let mut dump = String::new();
+    unsafe {
+        (registry::get().dump_dispatch_trace)(&mut dump as *mut String as *mut std::ffi::c_void, write);
+    }
+    dump
+}
+
+/// Colors of the group `agent`'s entry (or its owner's, for weapons) is using,
+/// empty if it isn't using one.
+pub fn get_colors(agent: &mut L2CAgentBase) -> Vec<i32> {
+    match registry::query(agent.module_accessor) {
+        Some(raw) if !raw.colors.is_null() => unsafe { std::slice::from_raw_parts(raw.colors, raw.colors_len) }.to_vec(),
+        _ => Vec::new(),
     }
-    Vec::new()
 }
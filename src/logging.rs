@@ -0,0 +1,105 @@
+//! Everything this crate reports goes through the `log` crate, at these levels:
+//!
+//! - `error`/`warn`: registrations that were ignored or can never run
+//! - `info`: slot resolution, loaded config files
+//! - `debug`: registrations, status overrides
+//! - `trace`: every ACMD dispatch and weapon owner lookup
+//!
+//! Records about a specific agent use `one_slot_agent::agent::<name>` as their
+//! target, e.g. `one_slot_agent::agent::mario_fireball`. Any `log` logger works;
+//! `init` installs one that prints and appends to `LOG_PATH`.
+
+use std::fs::File;
+use std::io::Write;
+use std::sync::Mutex;
+use log::{LevelFilter, Log, Metadata, Record};
+
+pub const LOG_PATH: &str = "sd:/ultimate/one_slot_agent/log.txt";
+const AGENT_TARGET: &str = "one_slot_agent::agent::";
+
+macro_rules! agent_log {
+    ($level:ident, $agent:expr, $($arg:tt)+) => {
+        log::$level!(target: $crate::logging::agent_target($agent).as_str(), $($arg)+)
+    };
+}
+pub(crate) use agent_log;
+
+pub(crate) fn agent_target(agent: &str) -> String {
+    AGENT_TARGET.to_owned() + agent
+}
+
+// Name of the fighter or weapon `hash` is the kind hash of, for log targets.
+pub(crate) fn agent_name(hash: u64) -> &'static str {
+    crate::util::get_fighter_id_from_hash(hash)
+        .and_then(crate::util::get_fighter_name)
+        .or_else(|| crate::util::get_weapon_id_from_hash(hash).and_then(crate::util::get_weapon_name))
+        .unwrap_or("unknown")
+}
+
+struct SlotLogger {
+    file: Mutex<Option<File>>,
+}
+
+static LOGGER: SlotLogger = SlotLogger {
+    file: Mutex::new(None),
+};
+
+static mut AGENT_FILTER: Vec<String> = Vec::new();
+
+impl Log for SlotLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match metadata.target().strip_prefix(AGENT_TARGET) {
+            Some(agent) => unsafe { AGENT_FILTER.is_empty() || AGENT_FILTER.iter().any(|a| a == agent) },
+            None => true,
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!("[{}] [{}] {}", record.level(), record.target(), record.args());
+        println!("{}", line);
+
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// Installs a logger that prints every record up to `level` and appends it to
+/// `LOG_PATH`. Does nothing if the plugin already installed a logger.
+pub fn init(level: LevelFilter) {
+    if log::set_logger(&LOGGER).is_err() {
+        return;
+    }
+    log::set_max_level(level);
+
+    let file = std::fs::create_dir_all("sd:/ultimate/one_slot_agent")
+        .and_then(|_| std::fs::OpenOptions::new().create(true).append(true).open(LOG_PATH));
+
+    match file {
+        Ok(file) => *LOGGER.file.lock().unwrap() = Some(file),
+        Err(e) => log::warn!("Couldn't open {}: {}", LOG_PATH, e),
+    }
+}
+
+/// Only keeps records about the listed agents (and records not about any
+/// agent). An empty list keeps everything. Only applies to the logger
+/// installed by `init`.
+pub fn set_agent_filter(agents: &[&str]) {
+    unsafe {
+        AGENT_FILTER = agents.iter().map(|agent| agent.to_string()).collect();
+    }
+}
@@ -0,0 +1,743 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::c_void;
+use once_cell::sync::Lazy;
+use smashline::*;
+use smash::hash40;
+use smash::app::BattleObjectModuleAccessor;
+
+use crate::selector::{self, RawSelector};
+use crate::{SLOTTED_AGENTS, INSTALLED_AGENTS, ACMD_BASE_NAME, SLOT_GROUPS, SlotGroup, SlottedInfo, AcmdScript, AcmdPlacement, StatusScript, config, installer, logging, trace, util};
+use crate::logging::agent_log;
+
+// Bumped whenever the layout of `Registry` or `RawGroup` changes, so copies
+// built against different versions of this crate never talk to each other.
+const REGISTRY_VERSION: u32 = 17;
+const REGISTRY_SYMBOL: &str = "one_slot_agent_registry\0";
+
+#[repr(C)]
+pub(crate) struct RawGroup {
+    pub agent_hash: u64,
+    pub colors: *const i32,
+    pub colors_len: usize,
+    pub color_bool: *const [bool; 256],
+    pub label: *const u8,
+    pub label_len: usize,
+    pub selector: RawSelector,
+    pub variant_pool: *const u8,
+    pub variant_pool_len: usize,
+    pub variant_weight: u32,
+}
+
+#[repr(C)]
+pub(crate) struct Registry {
+    pub version: u32,
+    pub register_group: unsafe extern "C" fn(&RawGroup),
+    pub register_acmd: unsafe extern "C" fn(&RawGroup, *const u8, usize, i32, i32, AcmdFunction),
+    pub register_status: unsafe extern "C" fn(&RawGroup, i32, i32, *const ()),
+    pub register_frame: unsafe extern "C" fn(&RawGroup, *const ()),
+    pub register_on_start: unsafe extern "C" fn(&RawGroup, *const ()),
+    pub register_kirby_copy: unsafe extern "C" fn(&RawGroup, i32),
+    pub install: unsafe extern "C" fn(&RawGroup, *const u8, usize, bool, bool),
+    pub query_slot: unsafe extern "C" fn(*mut BattleObjectModuleAccessor, &mut RawSlotHandle) -> bool,
+    pub find_group: unsafe extern "C" fn(*const u8, usize, &mut usize) -> bool,
+    pub set_active_slot: unsafe extern "C" fn(&mut L2CFighterCommon, usize, bool) -> bool,
+    pub register_on_reload: unsafe extern "C" fn(extern "C" fn()),
+    pub reload: unsafe extern "C" fn(),
+    pub slot_report: unsafe extern "C" fn(*mut c_void, unsafe extern "C" fn(*mut c_void, *const u8, usize)),
+    pub set_debug_display: unsafe extern "C" fn(extern "C" fn(*const u8, usize)),
+    pub set_dispatch_trace: unsafe extern "C" fn(bool),
+    pub dump_dispatch_trace: unsafe extern "C" fn(*mut c_void, unsafe extern "C" fn(*mut c_void, *const u8, usize)),
+    pub set_variant_seed: unsafe extern "C" fn(u64, bool),
+    pub variant_seed: unsafe extern "C" fn(&mut u64) -> bool,
+    pub set_name_tag_source: unsafe extern "C" fn(selector::RawNameTagSource),
+    pub has_name_tag_source: unsafe extern "C" fn() -> bool,
+    pub name_tag: unsafe extern "C" fn(i32, *mut c_void, unsafe extern "C" fn(*mut c_void, *const u8, usize)) -> bool,
+}
+
+#[repr(C)]
+pub(crate) struct RawSlotHandle {
+    pub entry_id: usize,
+    pub group: usize,
+    pub generation: u64,
+    pub label: *const u8,
+    pub label_len: usize,
+    // Colors of the group as the queried agent has it registered, or as the
+    // group was registered if the agent doesn't have it.
+    pub colors: *const i32,
+    pub colors_len: usize,
+    pub inherited: bool,
+    pub variant: usize,
+    pub has_variant: bool,
+}
+
+impl Default for RawSlotHandle {
+    fn default() -> Self {
+        Self {
+            entry_id: 0,
+            group: 0,
+            generation: 0,
+            label: std::ptr::null(),
+            label_len: 0,
+            colors: std::ptr::null(),
+            colors_len: 0,
+            inherited: false,
+            variant: 0,
+            has_variant: false,
+        }
+    }
+}
+
+static LOCAL_REGISTRY: Registry = Registry {
+    version: REGISTRY_VERSION,
+    register_group,
+    register_acmd,
+    register_status,
+    register_frame,
+    register_on_start,
+    register_kirby_copy,
+    install,
+    query_slot,
+    find_group,
+    set_active_slot,
+    register_on_reload,
+    reload,
+    slot_report,
+    set_debug_display,
+    set_dispatch_trace,
+    dump_dispatch_trace,
+    set_variant_seed,
+    variant_seed,
+    set_name_tag_source,
+    has_name_tag_source,
+    name_tag,
+};
+
+static mut SHARED_ENABLED: bool = false;
+// Set once something was registered into the local registry without sharing.
+static mut LOCAL_IN_USE: bool = false;
+static mut SHARED_REGISTRY: Option<&'static Registry> = None;
+
+#[no_mangle]
+pub extern "C" fn one_slot_agent_registry() -> *const Registry {
+    &LOCAL_REGISTRY
+}
+
+pub(crate) fn enable_shared() {
+    unsafe {
+        if LOCAL_IN_USE && !SHARED_ENABLED {
+            log::warn!("`enable_shared_registry` was called after agents were registered, ignoring it");
+            return;
+        }
+        SHARED_ENABLED = true;
+    }
+}
+
+pub(crate) fn get() -> &'static Registry {
+    unsafe {
+        if !SHARED_ENABLED {
+            LOCAL_IN_USE = true;
+            return &LOCAL_REGISTRY;
+        }
+
+        if let Some(registry) = SHARED_REGISTRY {
+            return registry;
+        }
+
+        let mut addr: usize = 0;
+        let registry = if skyline::nn::ro::LookupSymbol(&mut addr, REGISTRY_SYMBOL.as_ptr() as _) == 0 && addr != 0 {
+            let f: extern "C" fn() -> *const Registry = std::mem::transmute(addr);
+            let registry = &*f();
+
+            if registry.version == REGISTRY_VERSION {
+                registry
+            } else {
+                log::warn!(
+                    "Shared registry version mismatch (found {}, expected {}), using local registry",
+                    registry.version,
+                    REGISTRY_VERSION
+                );
+                &LOCAL_REGISTRY
+            }
+        } else {
+            &LOCAL_REGISTRY
+        };
+
+        SHARED_REGISTRY = Some(registry);
+        registry
+    }
+}
+
+pub(crate) fn query(boma: *mut BattleObjectModuleAccessor) -> Option<RawSlotHandle> {
+    let mut raw = RawSlotHandle::default();
+
+    if unsafe { (get().query_slot)(boma, &mut raw) } {
+        Some(raw)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn is_local(registry: &Registry) -> bool {
+    std::ptr::eq(registry, &LOCAL_REGISTRY)
+}
+
+impl RawGroup {
+    pub(crate) fn new(agent_hash: u64, colors: &[i32], color_bool: Option<*const [bool; 256]>, label: Option<&str>) -> Self {
+        Self {
+            agent_hash,
+            colors: colors.as_ptr(),
+            colors_len: colors.len(),
+            color_bool: color_bool.unwrap_or(std::ptr::null()),
+            label: label.map_or(std::ptr::null(), str::as_ptr),
+            label_len: label.map_or(0, str::len),
+            selector: RawSelector::NONE,
+            variant_pool: std::ptr::null(),
+            variant_pool_len: 0,
+            variant_weight: 0,
+        }
+    }
+
+    unsafe fn variant(&self) -> Option<(&str, u32)> {
+        if self.variant_pool.is_null() {
+            None
+        } else {
+            Some((str_from_raw(self.variant_pool, self.variant_pool_len), self.variant_weight))
+        }
+    }
+
+    unsafe fn label(&self) -> Option<&str> {
+        if self.label.is_null() {
+            None
+        } else {
+            Some(str_from_raw(self.label, self.label_len))
+        }
+    }
+
+    unsafe fn colors(&self) -> &[i32] {
+        if self.colors.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(self.colors, self.colors_len)
+        }
+    }
+}
+
+pub(crate) unsafe fn str_from_raw<'a>(ptr: *const u8, len: usize) -> &'a str {
+    std::str::from_utf8_unchecked(std::slice::from_raw_parts(ptr, len))
+}
+
+// Groups are shared between agents: a fighter and its weapons registered with
+// the same colors get the same id, which is what weapons resolve through.
+unsafe fn group_id(group: &RawGroup) -> usize {
+    let colors = group.colors();
+    let color_bool = if group.color_bool.is_null() { None } else { Some(group.color_bool) };
+    let selector = group.selector.get();
+    let label = group.label();
+    let variant = group.variant();
+    let mut slot_groups = SLOT_GROUPS.write();
+
+    if let Some(id) = slot_groups.iter().position(|g| {
+        g.color.as_slice() == colors
+            && g.color_bool == color_bool
+            && g.selector == selector
+            && g.variant.as_ref().map(|(pool, weight)| (pool.as_str(), *weight)) == variant
+            && g.copy_of.is_none()
+    }) {
+        let slot_group = &mut slot_groups[id];
+
+        if let Some(label) = label {
+            match &slot_group.label {
+                Some(existing) if existing != label => {
+                    log::warn!(
+                        "Slot group {} with colors {:?} is already labeled \"{}\", ignoring label \"{}\"",
+                        id,
+                        colors,
+                        existing,
+                        label
+                    );
+                }
+                Some(_) => {}
+                None => slot_group.label = Some(label.to_string()),
+            }
+        }
+        id
+    } else {
+        slot_groups.push(SlotGroup {
+            color: colors.to_vec(),
+            color_bool,
+            selector,
+            label: label.map(str::to_string),
+            variant: variant.map(|(pool, weight)| (pool.to_string(), weight)),
+            copy_of: None,
+        });
+        slot_groups.len() - 1
+    }
+}
+
+// Kirby's copy of `group` for `fighter_id`'s ability. Kept apart from `group`
+// itself so Kirby's own color never resolves to it by accident.
+fn copy_group_id(group: usize, fighter_id: i32, mode: i32) -> usize {
+    let mut slot_groups = SLOT_GROUPS.write();
+    let copy_of = Some((group, fighter_id, mode));
+
+    if let Some(id) = slot_groups.iter().position(|g| g.copy_of == copy_of) {
+        id
+    } else {
+        let slot_group = SlotGroup {
+            color: slot_groups[group].color.clone(),
+            color_bool: slot_groups[group].color_bool,
+            selector: slot_groups[group].selector,
+            label: slot_groups[group].label.clone(),
+            variant: slot_groups[group].variant.clone(),
+            copy_of,
+        };
+        slot_groups.push(slot_group);
+        slot_groups.len() - 1
+    }
+}
+
+unsafe fn with_group<F: Fn(&mut SlottedInfo)>(group: &RawGroup, f: F) {
+    let colors = group.colors();
+    let id = group_id(group);
+    let mut slotted_agents = SLOTTED_AGENTS.write();
+
+    // Registering one fighter of a pair registers both, so a one-slot Ice
+    // Climbers moveset doesn't leave Nana on vanilla scripts. Movesets that
+    // register the partner themselves keep their own scripts.
+    let partner_hash = util::get_fighter_id_from_hash(group.agent_hash)
+        .and_then(util::get_fighter_name)
+        .and_then(util::get_partner_name)
+        .and_then(|partner| util::get_fighter_hash(util::get_fighter_id(partner)));
+
+    for (agent_hash, mirrored) in [(group.agent_hash, false)].into_iter().chain(partner_hash.map(|hash| (hash, true))) {
+        let slotted_info = slotted_agents.entry(agent_hash).or_default();
+
+        if let Some(info) = slotted_info.iter_mut().find(|info| info.group == id) {
+            if mirrored && !info.mirrored {
+                continue;
+            }
+            if !mirrored && info.mirrored {
+                info.frame = None;
+                info.on_start = None;
+                info.acmds.clear();
+                info.statuses.clear();
+                info.mirrored = false;
+            }
+            f(info);
+        } else {
+            let mut info = SlottedInfo {
+                group: id,
+                color: colors.to_vec(),
+                registered_color: colors.to_vec(),
+                color_bool: if group.color_bool.is_null() { None } else { Some(group.color_bool) },
+                selector: group.selector.get(),
+                frame: None,
+                on_start: None,
+                acmds: HashMap::new(),
+                statuses: vec![],
+                copy_of: None,
+                mirrored,
+            };
+            f(&mut info);
+            slotted_info.push(info);
+        }
+    }
+}
+
+fn acmd_category(category: i32) -> Option<Acmd> {
+    [Acmd::Game, Acmd::Effect, Acmd::Sound, Acmd::Expression]
+        .into_iter()
+        .find(|c| *c as i32 == category)
+}
+
+unsafe extern "C" fn register_group(group: &RawGroup) {
+    with_group(group, |_| {});
+}
+
+fn acmd_placement(placement: i32) -> Option<AcmdPlacement> {
+    [AcmdPlacement::Replace, AcmdPlacement::Before, AcmdPlacement::After]
+        .into_iter()
+        .find(|p| *p as i32 == placement)
+}
+
+unsafe extern "C" fn register_acmd(group: &RawGroup, name: *const u8, name_len: usize, category: i32, placement: i32, function: AcmdFunction) {
+    let name = str_from_raw(name, name_len);
+
+    let Some(category) = acmd_category(category) else {
+        agent_log!(warn, logging::agent_name(group.agent_hash), "ACMD Category for {} could not be found! Skipping...", name);
+        return;
+    };
+    let Some(placement) = acmd_placement(placement) else {
+        agent_log!(warn, logging::agent_name(group.agent_hash), "Unknown placement {} for {}! Skipping...", placement, name);
+        return;
+    };
+
+    let hash = hash40(name);
+    let id = group_id(group);
+    agent_log!(debug, logging::agent_name(group.agent_hash), "Registered {} for group {}", name, id);
+
+    with_group(group, |info| {
+        info.acmds.insert(hash, AcmdScript { category, function, placement });
+    });
+
+    if let Some((_, base_name)) = name.split_once('_') {
+        let game = "game_".to_owned() + base_name;
+        ACMD_BASE_NAME
+            .write()
+            .entry(hash40(&game))
+            .or_insert_with(|| {
+                base_name.to_string()
+            });
+    }
+}
+
+unsafe extern "C" fn register_status(group: &RawGroup, line: i32, kind: i32, function: *const ()) {
+    agent_log!(debug, logging::agent_name(group.agent_hash), "Registered status {}:{} for group {}", kind, line, group_id(group));
+    with_group(group, |info| {
+        info.statuses.push(StatusScript { line, kind, function });
+    });
+}
+
+unsafe extern "C" fn register_frame(group: &RawGroup, function: *const ()) {
+    agent_log!(debug, logging::agent_name(group.agent_hash), "Registered frame function for group {}", group_id(group));
+    with_group(group, |info| {
+        info.frame = Some(function);
+    });
+}
+
+unsafe extern "C" fn register_on_start(group: &RawGroup, function: *const ()) {
+    agent_log!(debug, logging::agent_name(group.agent_hash), "Registered on_start for group {}", group_id(group));
+    with_group(group, |info| {
+        info.on_start = Some(function);
+    });
+}
+
+unsafe extern "C" fn register_kirby_copy(group: &RawGroup, mode: i32) {
+    let Some(fighter_id) = util::get_fighter_id_from_hash(group.agent_hash) else {
+        return;
+    };
+    let fighter = util::get_fighter_name(fighter_id).unwrap_or_default();
+    let kirby = "kirby";
+    let kirby_hash = hash40("fighter_kind_kirby");
+
+    let id = group_id(group);
+    let mut slotted_agents = SLOTTED_AGENTS.write();
+
+    let Some((color, color_bool, selector, acmds)) = slotted_agents
+        .get(&group.agent_hash)
+        .and_then(|slotted_info| slotted_info.iter().find(|info| info.group == id))
+        .map(|info| {
+            if !info.statuses.is_empty() {
+                agent_log!(
+                    warn,
+                    fighter,
+                    "Kirby only copies the ACMD of `{}`'s group {}, its {} status scripts aren't copied",
+                    fighter,
+                    id,
+                    info.statuses.len()
+                );
+            }
+
+            // Kirby's copy scripts are named after the fighter, e.g.
+            // `game_specialn` on `mario` is `game_mariospecialn` on `kirby`.
+            let mut acmds = HashMap::new();
+            let mut acmd_base_name = ACMD_BASE_NAME.write();
+            let base_names: Vec<String> = acmd_base_name
+                .values()
+                .filter(|base_name| base_name.starts_with("specialn") || base_name.starts_with("specialairn"))
+                .cloned()
+                .collect();
+
+            for base_name in base_names {
+                let kirby_base_name = fighter.to_owned() + &base_name;
+
+                for category in ["game", "effect", "sound", "expression"] {
+                    if let Some(script) = info.acmds.get(&hash40(&format!("{}_{}", category, base_name))) {
+                        acmds.insert(hash40(&format!("{}_{}", category, kirby_base_name)), *script);
+                    }
+                }
+
+                acmd_base_name
+                    .entry(hash40(&format!("game_{}", kirby_base_name)))
+                    .or_insert(kirby_base_name);
+            }
+
+            (info.color.clone(), info.color_bool, info.selector, acmds)
+        })
+    else {
+        agent_log!(warn, fighter, "`{}` has no slotted scripts for Kirby to copy", fighter);
+        return;
+    };
+
+    let copy_group = copy_group_id(id, fighter_id, mode);
+    let kirby_info = slotted_agents.entry(kirby_hash).or_default();
+
+    if let Some(info) = kirby_info.iter_mut().find(|info| info.group == copy_group) {
+        info.acmds.extend(acmds);
+    } else {
+        kirby_info.push(SlottedInfo {
+            group: copy_group,
+            registered_color: color.clone(),
+            color,
+            color_bool,
+            selector,
+            frame: None,
+            on_start: None,
+            acmds,
+            statuses: vec![],
+            copy_of: Some((fighter_id, mode)),
+            mirrored: false,
+        });
+    }
+    drop(slotted_agents);
+
+    let kirby_group = RawGroup::new(kirby_hash, &[], None, None);
+    install(&kirby_group, kirby.as_ptr(), kirby.len(), false, false);
+}
+
+unsafe extern "C" fn install(group: &RawGroup, name: *const u8, name_len: usize, is_weapon: bool, is_cloned: bool) {
+    let name = str_from_raw(name, name_len);
+    let mut agent = Agent::new(name);
+
+    if install_slotted(&mut agent, group, is_weapon, is_cloned) {
+        agent.install();
+    }
+}
+
+unsafe extern "C" fn query_slot(boma: *mut BattleObjectModuleAccessor, out: &mut RawSlotHandle) -> bool {
+    if let Some(slot) = installer::query_slot(boma) {
+        out.entry_id = slot.entry_id;
+        out.group = slot.group;
+        out.generation = slot.generation;
+        out.inherited = slot.inherited;
+
+        let slot_groups = SLOT_GROUPS.read();
+        if let Some(label) = slot_groups.get(slot.group).and_then(|g| g.label.as_ref()) {
+            out.label = label.as_ptr();
+            out.label_len = label.len();
+        }
+
+        // Kirby's copy groups are the variant of the group they copy.
+        let group = slot_groups
+            .get(slot.group)
+            .and_then(|g| g.copy_of)
+            .map_or(slot.group, |(group, _, _)| group);
+        if let Some((pool, _)) = slot_groups.get(group).and_then(|g| g.variant.as_ref()) {
+            out.variant = slot_groups
+                .iter()
+                .enumerate()
+                .filter(|(_, g)| g.copy_of.is_none() && g.variant.as_ref().map_or(false, |(p, _)| p == pool))
+                .position(|(id, _)| id == group)
+                .unwrap_or_default();
+            out.has_variant = true;
+        }
+
+        let kind = smash::app::utility::get_kind(&mut *boma);
+        let agent_hash = if slot.inherited { util::get_weapon_hash(kind) } else { util::get_fighter_hash(kind) };
+        let slotted_agents = SLOTTED_AGENTS.read();
+        let colors = agent_hash
+            .and_then(|hash| slotted_agents.get(&hash))
+            .and_then(|slotted_info| slotted_info.iter().find(|info| info.group == slot.group))
+            .map(|info| &info.color)
+            .or_else(|| slot_groups.get(slot.group).map(|g| &g.color));
+
+        // Copied by the caller right away, before a reload can replace them.
+        if let Some(colors) = colors {
+            out.colors = colors.as_ptr();
+            out.colors_len = colors.len();
+        }
+        true
+    } else {
+        false
+    }
+}
+
+unsafe extern "C" fn find_group(label: *const u8, label_len: usize, out: &mut usize) -> bool {
+    let label = str_from_raw(label, label_len);
+
+    if let Some(id) = SLOT_GROUPS
+        .read()
+        .iter()
+        .position(|g| g.copy_of.is_none() && g.label.as_deref() == Some(label))
+    {
+        *out = id;
+        true
+    } else {
+        false
+    }
+}
+
+unsafe extern "C" fn set_active_slot(fighter: &mut L2CFighterCommon, group: usize, has_group: bool) -> bool {
+    installer::set_active_slot(fighter, if has_group { Some(group) } else { None })
+}
+
+unsafe extern "C" fn register_on_reload(f: extern "C" fn()) {
+    config::add_reload_callback(f);
+}
+
+unsafe extern "C" fn reload() {
+    installer::request_reload();
+}
+
+unsafe extern "C" fn slot_report(ctx: *mut c_void, out: unsafe extern "C" fn(*mut c_void, *const u8, usize)) {
+    let report = installer::slot_report();
+    out(ctx, report.as_ptr(), report.len());
+}
+
+unsafe extern "C" fn set_debug_display(f: extern "C" fn(*const u8, usize)) {
+    installer::set_debug_display(f);
+}
+
+unsafe extern "C" fn set_dispatch_trace(enabled: bool) {
+    trace::set_enabled(enabled);
+}
+
+unsafe extern "C" fn dump_dispatch_trace(ctx: *mut c_void, out: unsafe extern "C" fn(*mut c_void, *const u8, usize)) {
+    let dump = trace::dump();
+    out(ctx, dump.as_ptr(), dump.len());
+}
+
+unsafe extern "C" fn set_variant_seed(seed: u64, has_seed: bool) {
+    selector::set_fixed_variant_seed(if has_seed { Some(seed) } else { None });
+}
+
+unsafe extern "C" fn set_name_tag_source(source: selector::RawNameTagSource) {
+    selector::set_shared_name_tag_source(source);
+}
+
+unsafe extern "C" fn has_name_tag_source() -> bool {
+    selector::shared_name_tag_source().is_some()
+}
+
+unsafe extern "C" fn name_tag(
+    entry_id: i32,
+    ctx: *mut c_void,
+    out: unsafe extern "C" fn(*mut c_void, *const u8, usize),
+) -> bool {
+    match selector::shared_name_tag_source() {
+        Some(source) => source(entry_id, ctx, out),
+        None => false,
+    }
+}
+
+unsafe extern "C" fn variant_seed(out: &mut u64) -> bool {
+    match selector::current_variant_seed() {
+        Some(seed) => {
+            *out = seed;
+            true
+        }
+        None => false,
+    }
+}
+
+// Hooks already added to each weapon, by (weapon hash, status kind), with
+// `WEAPON_FRAME_HOOK` for its frame function.
+static mut WEAPON_HOOKS: Lazy<HashSet<(u64, i32)>> = Lazy::new(HashSet::new);
+const WEAPON_FRAME_HOOK: i32 = -1;
+
+// Adds the one-slot installers to `agent`. Returns `false` if nothing had to be
+// added, i.e. this agent was already handled by this registry. Weapons can be
+// installed again once they have a frame function or more cloned statuses,
+// e.g. after `with_weapons` installed them with nothing registered yet.
+pub(crate) unsafe fn install_slotted(agent: &mut Agent, group: &RawGroup, is_weapon: bool, is_cloned: bool) -> bool {
+    let installed = INSTALLED_AGENTS.contains(&group.agent_hash);
+    if installed && !is_weapon {
+        return false;
+    }
+
+    if is_weapon {
+        let hash = group.agent_hash;
+        let f = if is_cloned {
+            installer::slotted_cloned_weapon_installer_pre
+        } else {
+            installer::slotted_weapon_installer_pre
+        };
+
+        let mut added = false;
+        let mut add_hook = |kind: i32| {
+            let new = WEAPON_HOOKS.insert((hash, kind));
+            added |= new;
+            new
+        };
+
+        if add_hook(0) {
+            agent.status(Pre, 0, f);
+        }
+
+        if let Some(slotted_info) = SLOTTED_AGENTS.read().get(&hash) {
+            if is_cloned {
+                for status in slotted_info.iter().flat_map(|info| info.statuses.iter()) {
+                    if status.kind != 0 && add_hook(status.kind) {
+                        agent.status(Pre, status.kind, f);
+                    }
+                }
+            }
+
+            if slotted_info.iter().any(|info| info.frame.is_some()) && add_hook(WEAPON_FRAME_HOOK) {
+                agent.on_line(Main, installer::weapon_opff);
+            }
+        }
+
+        if installed {
+            return added;
+        }
+    } else {
+        agent.on_start(installer::on_start);
+        agent.on_line(Main, installer::opff);
+    }
+
+    let uuid: String = crate::UUID.iter().collect();
+    agent.acmd(&format!("game_acmd_installer{}", uuid), installer::game_acmd_installer, Priority::Default);
+    agent.acmd(&format!("effect_acmd_installer{}", uuid), installer::effect_acmd_installer, Priority::Default);
+    agent.acmd(&format!("sound_acmd_installer{}", uuid), installer::sound_acmd_installer, Priority::Default);
+    agent.acmd(&format!("expression_acmd_installer{}", uuid), installer::expression_acmd_installer, Priority::Default);
+
+    INSTALLED_AGENTS.insert(group.agent_hash);
+
+    if !is_weapon {
+        let partner = util::get_fighter_id_from_hash(group.agent_hash)
+            .and_then(util::get_fighter_name)
+            .and_then(util::get_partner_name);
+
+        if let Some(partner) = partner {
+            let partner_hash = util::get_fighter_hash(util::get_fighter_id(partner)).unwrap_or_default();
+            let partner_group = RawGroup::new(partner_hash, group.colors(), None, None);
+            install(&partner_group, partner.as_ptr(), partner.len(), false, false);
+        }
+    }
+    true
+}
+
+// Weapons only ever resolve through their owner's slot, so a weapon group the
+// owner doesn't have is dead. Called once the first match starts, when every
+// plugin is done registering.
+pub(crate) fn validate_weapon_owners() {
+    let slotted_agents = SLOTTED_AGENTS.read();
+
+    for (hash, slotted_info) in slotted_agents.iter() {
+        let Some(weapon_id) = util::get_weapon_id_from_hash(*hash) else {
+            continue;
+        };
+        let weapon = util::get_weapon_name(weapon_id).unwrap_or_default();
+
+        let Some(owner_id) = util::get_weapon_owner_id(weapon_id) else {
+            agent_log!(warn, weapon, "Couldn't find the owner of slotted weapon `{}`", weapon);
+            continue;
+        };
+        let owner = util::get_fighter_name(owner_id).unwrap_or_default();
+        let owner_info = util::get_fighter_hash(owner_id).and_then(|hash| slotted_agents.get(&hash));
+
+        for info in slotted_info.iter() {
+            if !owner_info.is_some_and(|owner_info| owner_info.iter().any(|o| o.group == info.group)) {
+                agent_log!(
+                    warn,
+                    weapon,
+                    "`{}` is slotted for colors {:?} but its owner `{}` isn't, so those scripts will never run",
+                    weapon,
+                    info.color,
+                    owner
+                );
+            }
+        }
+    }
+}
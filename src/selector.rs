@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::ffi::c_void;
+use once_cell::sync::Lazy;
+use smash::app::{lua_bind::*, *};
+use smash::lib::lua_const::*;
+
+/// Decides whether an entry uses a group. Evaluated once per entry when its
+/// slot is resolved, on the entry's fighter.
+pub trait SlotSelector: Send + Sync + 'static {
+    fn matches(&self, boma: *mut BattleObjectModuleAccessor) -> bool;
+}
+
+impl<F: Fn(*mut BattleObjectModuleAccessor) -> bool + Send + Sync + 'static> SlotSelector for F {
+    fn matches(&self, boma: *mut BattleObjectModuleAccessor) -> bool {
+        self(boma)
+    }
+}
+
+// Type-erased selector that can be handed to another copy of this crate, or
+// be provided by a C plugin.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(crate) struct RawSelector {
+    pub ctx: *const c_void,
+    pub matches: Option<unsafe extern "C" fn(*const c_void, *mut BattleObjectModuleAccessor) -> bool>,
+}
+
+impl RawSelector {
+    pub(crate) const NONE: Self = Self {
+        ctx: std::ptr::null(),
+        matches: None,
+    };
+
+    pub(crate) fn new<S: SlotSelector>(selector: S) -> Self {
+        unsafe extern "C" fn call<S: SlotSelector>(ctx: *const c_void, boma: *mut BattleObjectModuleAccessor) -> bool {
+            (*(ctx as *const S)).matches(boma)
+        }
+
+        // Groups live for the rest of the game, and so do their selectors.
+        Self {
+            ctx: Box::leak(Box::new(selector)) as *const S as *const c_void,
+            matches: Some(call::<S>),
+        }
+    }
+
+    pub(crate) fn get(self) -> Option<Self> {
+        self.matches.map(|_| self)
+    }
+
+    pub(crate) unsafe fn matches(&self, boma: *mut BattleObjectModuleAccessor) -> bool {
+        self.matches.map_or(true, |matches| matches(self.ctx, boma))
+    }
+}
+
+impl PartialEq for RawSelector {
+    fn eq(&self, other: &Self) -> bool {
+        self.ctx == other.ctx && self.matches.map(|f| f as usize) == other.matches.map(|f| f as usize)
+    }
+}
+
+/// Matches Mii fighters by their chosen specials, 1 to 3 or `None` for any.
+pub struct MiiSpecials {
+    pub n: Option<i32>,
+    pub s: Option<i32>,
+    pub hi: Option<i32>,
+    pub lw: Option<i32>,
+}
+
+impl SlotSelector for MiiSpecials {
+    fn matches(&self, boma: *mut BattleObjectModuleAccessor) -> bool {
+        let work_ids = [
+            *FIGHTER_INSTANCE_WORK_ID_INT_CUSTOMIZE_SPECIAL_N_NO,
+            *FIGHTER_INSTANCE_WORK_ID_INT_CUSTOMIZE_SPECIAL_S_NO,
+            *FIGHTER_INSTANCE_WORK_ID_INT_CUSTOMIZE_SPECIAL_HI_NO,
+            *FIGHTER_INSTANCE_WORK_ID_INT_CUSTOMIZE_SPECIAL_LW_NO,
+        ];
+
+        [self.n, self.s, self.hi, self.lw]
+            .into_iter()
+            .zip(work_ids)
+            .all(|(wanted, work_id)| {
+                wanted.map_or(true, |wanted| unsafe { WorkModule::get_int(boma, work_id) } + 1 == wanted)
+            })
+    }
+}
+
+/// Matches entries by their entry id (0 for player 1, and so on).
+pub struct Port(pub Vec<i32>);
+
+impl SlotSelector for Port {
+    fn matches(&self, boma: *mut BattleObjectModuleAccessor) -> bool {
+        let entry_id = unsafe { WorkModule::get_int(boma, *FIGHTER_INSTANCE_WORK_ID_INT_ENTRY_ID) };
+        self.0.contains(&entry_id)
+    }
+}
+
+/// Matches every entry, but only in training mode.
+pub struct TrainingMode;
+
+impl SlotSelector for TrainingMode {
+    fn matches(&self, _boma: *mut BattleObjectModuleAccessor) -> bool {
+        unsafe { smashball::is_training_mode() }
+    }
+}
+
+type NameTagSource = fn(i32) -> Option<String>;
+
+// Writes an entry's name tag through the callback, `false` if it has none.
+pub(crate) type RawNameTagSource = unsafe extern "C" fn(i32, *mut c_void, unsafe extern "C" fn(*mut c_void, *const u8, usize)) -> bool;
+
+// Set in the copy of the crate `set_name_tag_source` was called in.
+static mut NAME_TAG_SOURCE: Option<NameTagSource> = None;
+// Set in the copy the registry lives in, for every copy to read through.
+static mut SHARED_NAME_TAG_SOURCE: Option<RawNameTagSource> = None;
+
+/// Sets how `NameTag` reads an entry's name tag from its entry id, for every
+/// plugin using this crate. This is only a hook: the crate can't read name
+/// tags from the game by itself, so `NameTag` never matches until some plugin
+/// sets a source.
+pub fn set_name_tag_source(source: NameTagSource) {
+    unsafe extern "C" fn raw_source(
+        entry_id: i32,
+        ctx: *mut c_void,
+        out: unsafe extern "C" fn(*mut c_void, *const u8, usize),
+    ) -> bool {
+        match NAME_TAG_SOURCE.and_then(|source| source(entry_id)) {
+            Some(name) => {
+                out(ctx, name.as_ptr(), name.len());
+                true
+            }
+            None => false,
+        }
+    }
+
+    unsafe {
+        NAME_TAG_SOURCE = Some(source);
+        (crate::registry::get().set_name_tag_source)(raw_source);
+    }
+}
+
+pub(crate) unsafe fn set_shared_name_tag_source(source: RawNameTagSource) {
+    SHARED_NAME_TAG_SOURCE = Some(source);
+}
+
+pub(crate) unsafe fn shared_name_tag_source() -> Option<RawNameTagSource> {
+    SHARED_NAME_TAG_SOURCE
+}
+
+fn name_tag(entry_id: i32) -> Option<String> {
+    unsafe extern "C" fn write(ctx: *mut c_void, name: *const u8, len: usize) {
+        *(ctx as *mut String) = crate::registry::str_from_raw(name, len).to_string();
+    }
+
+    let mut name = String::new();
+    if unsafe { (crate::registry::get().name_tag)(entry_id, &mut name as *mut String as *mut c_void, write) } {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Matches entries by the name tag they picked, e.g. `NameTag::exact("ALT")`.
+pub struct NameTag {
+    tag: String,
+    prefix: bool,
+}
+
+impl NameTag {
+    pub fn exact(tag: &str) -> Self {
+        Self {
+            tag: tag.to_string(),
+            prefix: false,
+        }
+    }
+
+    pub fn prefix(tag: &str) -> Self {
+        Self {
+            tag: tag.to_string(),
+            prefix: true,
+        }
+    }
+}
+
+pub(crate) fn has_name_tag_source() -> bool {
+    unsafe { (crate::registry::get().has_name_tag_source)() }
+}
+
+impl SlotSelector for NameTag {
+    fn matches(&self, boma: *mut BattleObjectModuleAccessor) -> bool {
+        if !has_name_tag_source() {
+            static mut WARNED: bool = false;
+
+            unsafe {
+                if !WARNED {
+                    log::warn!("No name tag source is set, groups selected by name tag never match");
+                    WARNED = true;
+                }
+            }
+            return false;
+        }
+
+        let entry_id = unsafe { WorkModule::get_int(boma, *FIGHTER_INSTANCE_WORK_ID_INT_ENTRY_ID) };
+        match name_tag(entry_id) {
+            Some(name) if self.prefix => name.starts_with(&self.tag),
+            Some(name) => name == self.tag,
+            None => false,
+        }
+    }
+}
+
+// Group each entry rolled for every variant pool this match, by pool name.
+static mut VARIANT_ROLLS: Lazy<[HashMap<String, usize>; 8]> = Lazy::new(Default::default);
+static mut VARIANT_SEED: Option<u64> = None;
+static mut FIXED_VARIANT_SEED: Option<u64> = None;
+
+/// Makes every match roll the same variants, e.g. to reproduce a match from
+/// the seed it logged. `None` goes back to a new seed every match.
+pub fn set_variant_seed(seed: Option<u64>) {
+    unsafe {
+        (crate::registry::get().set_variant_seed)(seed.unwrap_or_default(), seed.is_some());
+    }
+}
+
+/// Seed the variants of the current match were rolled with.
+pub fn variant_seed() -> Option<u64> {
+    let mut seed = 0;
+
+    if unsafe { (crate::registry::get().variant_seed)(&mut seed) } {
+        Some(seed)
+    } else {
+        None
+    }
+}
+
+pub(crate) unsafe fn set_fixed_variant_seed(seed: Option<u64>) {
+    FIXED_VARIANT_SEED = seed;
+}
+
+pub(crate) unsafe fn current_variant_seed() -> Option<u64> {
+    VARIANT_SEED
+}
+
+pub(crate) unsafe fn clear_variant_rolls() {
+    VARIANT_ROLLS.iter_mut().for_each(HashMap::clear);
+    VARIANT_SEED = None;
+}
+
+pub(crate) unsafe fn rolled_variant(entry_id: usize, pool: &str) -> Option<usize> {
+    VARIANT_ROLLS.get(entry_id)?.get(pool).copied()
+}
+
+// Rolls one group for every variant pool of `slotted_info` the entry hasn't
+// rolled yet this match, among the groups `matches` accepts, e.g. those whose
+// colors match the entry.
+pub(crate) unsafe fn roll_variants<F: Fn(&crate::SlottedInfo) -> bool>(entry_id: usize, slotted_info: &[crate::SlottedInfo], matches: F) {
+    let slot_groups = crate::SLOT_GROUPS.read();
+    let rolls = &mut VARIANT_ROLLS[entry_id];
+    let mut pools: Vec<(&str, Vec<(usize, u32)>)> = Vec::new();
+
+    for info in slotted_info.iter().filter(|info| info.copy_of.is_none()) {
+        let Some((pool, weight)) = slot_groups.get(info.group).and_then(|g| g.variant.as_ref()) else {
+            continue;
+        };
+        if rolls.contains_key(pool) || !matches(info) {
+            continue;
+        }
+
+        match pools.iter_mut().find(|(name, _)| *name == pool.as_str()) {
+            Some((_, variants)) => variants.push((info.group, *weight)),
+            None => pools.push((pool.as_str(), vec![(info.group, *weight)])),
+        }
+    }
+
+    if pools.is_empty() {
+        return;
+    }
+
+    let seed = *VARIANT_SEED.get_or_insert_with(|| {
+        FIXED_VARIANT_SEED.unwrap_or_else(|| {
+            let mut seed: u64 = 0;
+            skyline::nn::os::GenerateRandomBytes(&mut seed as *mut u64 as *mut skyline::libc::c_void, 8);
+            seed
+        })
+    });
+
+    for (pool, variants) in pools {
+        let total: u64 = variants.iter().map(|&(_, w)| w as u64).sum();
+        let mut roll = mix(seed ^ mix(smash::hash40(pool) ^ entry_id as u64)) % total.max(1);
+        let group = variants
+            .iter()
+            .find(|&&(_, w)| {
+                if roll < w as u64 {
+                    true
+                } else {
+                    roll -= w as u64;
+                    false
+                }
+            })
+            .unwrap_or(&variants[0])
+            .0;
+
+        log::info!(
+            "Entry {} rolled group {} of variant pool \"{}\" (seed {:#018x})",
+            entry_id,
+            group,
+            pool,
+            seed
+        );
+        rolls.insert(pool.to_string(), group);
+    }
+}
+
+// splitmix64, good enough to spread the seed over entries and pools.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
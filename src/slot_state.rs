@@ -0,0 +1,66 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use once_cell::sync::Lazy;
+use smash::app::BattleObjectModuleAccessor;
+
+use crate::registry;
+
+struct StateEntry {
+    group: usize,
+    generation: u64,
+    // Set while `with_slot_state` hands the value out.
+    borrowed: bool,
+    value: Box<dyn Any>,
+}
+
+static mut SLOT_STATE: Lazy<[HashMap<TypeId, StateEntry>; 8]> = Lazy::new(Default::default);
+
+/// Runs `f` with per-entry storage for the slot `boma` (or its owner, for
+/// weapons) is using.
+///
+/// The value is created with `Default` the first time it's requested for a
+/// slot and is reset whenever the entry's slot is resolved again, i.e. at the
+/// start of every match. Returns `None` if the entry isn't using a slot, or if
+/// the same `T` of the entry is already borrowed by an enclosing call.
+///
+/// Like the rest of the game state, this is only meant to be used from the
+/// game thread.
+pub fn with_slot_state<T: Default + 'static, R>(boma: *mut BattleObjectModuleAccessor, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+    let slot = registry::query(boma)?;
+
+    let value = unsafe {
+        let states = &mut SLOT_STATE[slot.entry_id];
+        let state = states.entry(TypeId::of::<T>()).or_insert_with(|| StateEntry {
+            group: slot.group,
+            generation: slot.generation,
+            borrowed: false,
+            value: Box::new(T::default()),
+        });
+
+        if state.borrowed {
+            log::warn!("Slot state of entry {} is already borrowed", slot.entry_id);
+            return None;
+        }
+
+        if state.group != slot.group || state.generation != slot.generation {
+            state.group = slot.group;
+            state.generation = slot.generation;
+            state.value = Box::new(T::default());
+        }
+
+        // The value is boxed, so it stays put if `f` adds other types to the map.
+        let value = state.value.downcast_mut::<T>()? as *mut T;
+        state.borrowed = true;
+        value
+    };
+
+    let ret = f(unsafe { &mut *value });
+
+    unsafe {
+        // The entry itself may have moved, look it up again.
+        if let Some(state) = SLOT_STATE[slot.entry_id].get_mut(&TypeId::of::<T>()) {
+            state.borrowed = false;
+        }
+    }
+    Some(ret)
+}
@@ -0,0 +1,83 @@
+use std::collections::VecDeque;
+use std::fmt::Write;
+use once_cell::sync::Lazy;
+
+use crate::logging;
+
+const CAPACITY: usize = 256;
+
+#[derive(Clone, Copy)]
+pub(crate) enum Outcome {
+    // The agent has nothing slotted at all.
+    NotSlotted,
+    // No slotted script was registered under the motion's ACMD name.
+    UnknownMotion,
+    // The slotted script of this group ran.
+    Hit(usize),
+    // The entry's groups don't have the script, the original one ran.
+    Fallback,
+    // The entry's groups don't have the script and there was no original.
+    Miss,
+}
+
+pub(crate) struct Dispatch {
+    pub entry_id: i32,
+    pub agent_hash: u64,
+    pub category: &'static str,
+    pub motion_kind: u64,
+    pub game_hash: u64,
+    pub base_name: Option<String>,
+    pub outcome: Outcome,
+}
+
+static mut ENABLED: bool = false;
+static mut TRACE: Lazy<VecDeque<Dispatch>> = Lazy::new(|| VecDeque::with_capacity(CAPACITY));
+
+pub(crate) fn enabled() -> bool {
+    unsafe { ENABLED }
+}
+
+pub(crate) unsafe fn set_enabled(enabled: bool) {
+    ENABLED = enabled;
+    TRACE.clear();
+}
+
+// Keeps the last `CAPACITY` dispatches.
+pub(crate) unsafe fn record(dispatch: Dispatch) {
+    if TRACE.len() == CAPACITY {
+        TRACE.pop_front();
+    }
+    TRACE.push_back(dispatch);
+}
+
+// Oldest dispatch first.
+pub(crate) unsafe fn dump() -> String {
+    let mut dump = String::new();
+
+    for d in TRACE.iter() {
+        let outcome = match d.outcome {
+            Outcome::NotSlotted => "agent not slotted".to_string(),
+            Outcome::UnknownMotion => "no base name".to_string(),
+            Outcome::Hit(group) => format!("hit, group {}", group),
+            Outcome::Fallback => "miss, original run".to_string(),
+            Outcome::Miss => "miss, nothing run".to_string(),
+        };
+
+        let _ = writeln!(
+            dump,
+            "P{} {} motion {:#x} -> {:#x} {}_{}: {}",
+            d.entry_id + 1,
+            logging::agent_name(d.agent_hash),
+            d.motion_kind,
+            d.game_hash,
+            d.category,
+            d.base_name.as_deref().unwrap_or("?"),
+            outcome
+        );
+    }
+
+    if dump.is_empty() {
+        dump.push_str(if ENABLED { "No dispatches traced yet\n" } else { "Dispatch tracing is off\n" });
+    }
+    dump
+}
@@ -1,718 +1,1009 @@
-pub fn get_fighter_id(agent_name: &str) -> i32 {
-    match agent_name {
-        "mario" => 0,
-        "donkey" => 1,
-        "link" => 2,
-        "samus" => 3,
-        "samusd" => 4,
-        "yoshi" => 5,
-        "kirby" => 6,
-        "fox" => 7,
-        "pikachu" => 8,
-        "luigi" => 9,
-        "ness" => 10,
-        "captain" => 11,
-        "purin" => 12,
-        "peach" => 13,
-        "daisy" => 14,
-        "koopa" => 15,
-        "sheik" => 16,
-        "zelda" => 17,
-        "mariod" => 18,
-        "pichu" => 19,
-        "falco" => 20,
-        "marth" => 21,
-        "lucina" => 22,
-        "younglink" => 23,
-        "ganon" => 24,
-        "mewtwo" => 25,
-        "roy" => 26,
-        "chrom" => 27,
-        "gamewatch" => 28,
-        "metaknight" => 29,
-        "pit" => 30,
-        "pitb" => 31,
-        "szerosuit" => 32,
-        "wario" => 33,
-        "snake" => 34,
-        "ike" => 35,
-        "pzenigame" => 36,
-        "pfushigisou" => 37,
-        "plizardon" => 38,
-        "diddy" => 39,
-        "lucas" => 40,
-        "sonic" => 41,
-        "dedede" => 42,
-        "pikmin" => 43,
-        "lucario" => 44,
-        "robot" => 45,
-        "toonlink" => 46,
-        "wolf" => 47,
-        "murabito" => 48,
-        "rockman" => 49,
-        "wiifit" => 50,
-        "rosetta" => 51,
-        "littlemac" => 52,
-        "gekkouga" => 53,
-        "palutena" => 54,
-        "pacman" => 55,
-        "reflet" => 56,
-        "shulk" => 57,
-        "koopajr" => 58,
-        "duckhunt" => 59,
-        "ryu" => 60,
-        "ken" => 61,
-        "cloud" => 62,
-        "kamui" => 63,
-        "bayonetta" => 64,
-        "inkling" => 65,
-        "ridley" => 66,
-        "simon" => 67,
-        "richter" => 68,
-        "krool" => 69,
-        "shizue" => 70,
-        "gaogaen" => 71,
-        "miifighter" => 72,
-        "miiswordsman" => 73,
-        "miigunner" => 74,
-        "popo" => 75,
-        "nana" => 76,
-        "koopag" => 77,
-        "miienemyf" => 78,
-        "miienemys" => 79,
-        "miienemyg" => 80,
-        "packun" => 81,
-        "jack" => 82,
-        "brave" => 83,
-        "buddy" => 84,
-        "dolly" => 85,
-        "master" => 86,
-        "tantan" => 87,
-        "pickel" => 88,
-        "edge" => 89,
-        "eflame" => 90,
-        "elight" => 91,
-        "demon" => 92,
-        "trail" => 93,
-        "ice_climber" => 110,
-        "zenigame" => 111,
-        "fushigisou" => 112,
-        "lizardon" => 113,
-        "ptrainer" => 114,
-        "flame" => 115,
-        "light" => 116,
-        "element" => 117,
-        _ => -1
+use std::collections::HashMap;
+use once_cell::sync::Lazy;
+use smashline::locks::RwLock;
+use smash::hash40;
+
+use crate::logging::agent_log;
+
+pub static FIGHTER_KINDS: &[(&str, i32)] = &[
+    ("mario", 0),
+    ("donkey", 1),
+    ("link", 2),
+    ("samus", 3),
+    ("samusd", 4),
+    ("yoshi", 5),
+    ("kirby", 6),
+    ("fox", 7),
+    ("pikachu", 8),
+    ("luigi", 9),
+    ("ness", 10),
+    ("captain", 11),
+    ("purin", 12),
+    ("peach", 13),
+    ("daisy", 14),
+    ("koopa", 15),
+    ("sheik", 16),
+    ("zelda", 17),
+    ("mariod", 18),
+    ("pichu", 19),
+    ("falco", 20),
+    ("marth", 21),
+    ("lucina", 22),
+    ("younglink", 23),
+    ("ganon", 24),
+    ("mewtwo", 25),
+    ("roy", 26),
+    ("chrom", 27),
+    ("gamewatch", 28),
+    ("metaknight", 29),
+    ("pit", 30),
+    ("pitb", 31),
+    ("szerosuit", 32),
+    ("wario", 33),
+    ("snake", 34),
+    ("ike", 35),
+    ("pzenigame", 36),
+    ("pfushigisou", 37),
+    ("plizardon", 38),
+    ("diddy", 39),
+    ("lucas", 40),
+    ("sonic", 41),
+    ("dedede", 42),
+    ("pikmin", 43),
+    ("lucario", 44),
+    ("robot", 45),
+    ("toonlink", 46),
+    ("wolf", 47),
+    ("murabito", 48),
+    ("rockman", 49),
+    ("wiifit", 50),
+    ("rosetta", 51),
+    ("littlemac", 52),
+    ("gekkouga", 53),
+    ("palutena", 54),
+    ("pacman", 55),
+    ("reflet", 56),
+    ("shulk", 57),
+    ("koopajr", 58),
+    ("duckhunt", 59),
+    ("ryu", 60),
+    ("ken", 61),
+    ("cloud", 62),
+    ("kamui", 63),
+    ("bayonetta", 64),
+    ("inkling", 65),
+    ("ridley", 66),
+    ("simon", 67),
+    ("richter", 68),
+    ("krool", 69),
+    ("shizue", 70),
+    ("gaogaen", 71),
+    ("miifighter", 72),
+    ("miiswordsman", 73),
+    ("miigunner", 74),
+    ("popo", 75),
+    ("nana", 76),
+    ("koopag", 77),
+    ("miienemyf", 78),
+    ("miienemys", 79),
+    ("miienemyg", 80),
+    ("packun", 81),
+    ("jack", 82),
+    ("brave", 83),
+    ("buddy", 84),
+    ("dolly", 85),
+    ("master", 86),
+    ("tantan", 87),
+    ("pickel", 88),
+    ("edge", 89),
+    ("eflame", 90),
+    ("elight", 91),
+    ("demon", 92),
+    ("trail", 93),
+    ("ice_climber", 110),
+    ("zenigame", 111),
+    ("fushigisou", 112),
+    ("lizardon", 113),
+    ("ptrainer", 114),
+    ("flame", 115),
+    ("light", 116),
+    ("element", 117),
+];
+
+pub static WEAPON_KINDS: &[(&str, i32)] = &[
+    ("miifighter_hat", 0),
+    ("miifighter_ironball", 1),
+    ("miiswordsman_hat", 2),
+    ("miiswordsman_lightshuriken", 3),
+    ("miiswordsman_chakram", 4),
+    ("miiswordsman_tornadoshot", 5),
+    ("miiswordsman_wave", 6),
+    ("miigunner_hat", 7),
+    ("miigunner_rapidshot_bullet", 8),
+    ("miigunner_gunnercharge", 9),
+    ("miigunner_miimissile", 10),
+    ("miigunner_supermissile", 11),
+    ("miigunner_grenadelauncher", 12),
+    ("miigunner_flamepillar", 13),
+    ("miigunner_stealthbomb", 14),
+    ("miigunner_stealthbomb_s", 15),
+    ("miigunner_bottomshoot", 16),
+    ("miigunner_groundbomb", 17),
+    ("miigunner_attackairf_bullet", 18),
+    ("miigunner_laser", 19),
+    ("miigunner_fullthrottle", 20),
+    ("mario_fireball", 21),
+    ("mario_mantle", 22),
+    ("mario_pump", 23),
+    ("mario_pump_water", 24),
+    ("mario_huge_flame", 25),
+    ("mario_dokan", 26),
+    ("mario_cappy", 27),
+    ("donkey_dkbarrel", 28),
+    ("link_boomerang", 29),
+    ("link_bow", 30),
+    ("link_bowarrow", 31),
+    ("link_navy", 32),
+    ("link_sword_beam", 33),
+    ("link_ancient_bow", 34),
+    ("link_ancient_bowarrow", 35),
+    ("link_parasail", 36),
+    ("samus_cshot", 37),
+    ("samus_bomb", 38),
+    ("samus_missile", 39),
+    ("samus_laser", 40),
+    ("samus_laser2", 41),
+    ("samus_gun", 42),
+    ("samus_supermissile", 43),
+    ("samus_transportation", 44),
+    ("samus_gbeam", 45),
+    ("samusd_cshot", 46),
+    ("samusd_bomb", 47),
+    ("samusd_missile", 48),
+    ("samusd_laser", 49),
+    ("samusd_laser2", 50),
+    ("samusd_gun", 51),
+    ("samusd_supermissile", 52),
+    ("samusd_transportation", 53),
+    ("samusd_gbeam", 54),
+    ("samusd_bunshin", 55),
+    ("yoshi_star", 56),
+    ("yoshi_tamago", 57),
+    ("yoshi_yoshimob", 58),
+    ("yoshi_yoshibg01", 59),
+    ("kirby_hammer", 60),
+    ("kirby_finalcuttershot", 61),
+    ("kirby_starmissile", 62),
+    ("kirby_hat", 63),
+    ("kirby_ultrasword", 64),
+    ("kirby_ultraswordhat", 65),
+    ("kirby_warpstar", 66),
+    ("kirby_reserve", 67),
+    ("kirby_simple", 68),
+    ("kirby_miipartshead", 69),
+    ("kirby_rosettaticomissile", 70),
+    ("kirby_stone", 71),
+    ("kirby_simple2r", 72),
+    ("kirby_simple2l", 73),
+    ("kirby_windummy", 74),
+    ("kirby_finalcutter", 75),
+    ("pikachu_dengekidama", 76),
+    ("pikachu_dengeki", 77),
+    ("pikachu_kaminari", 78),
+    ("pikachu_cloud", 79),
+    ("pikachu_vortex", 80),
+    ("pikachu_monsterball", 81),
+    ("pikachu_specialupdummy", 82),
+    ("luigi_fireball", 83),
+    ("luigi_dokan", 84),
+    ("luigi_obakyumu", 85),
+    ("luigi_plunger", 86),
+    ("captain_bluefalcon", 87),
+    ("captain_falconpunch", 88),
+    ("fox_blaster", 89),
+    ("fox_blaster_bullet", 90),
+    ("fox_illusion", 91),
+    ("fox_arwing", 92),
+    ("fox_reticle", 93),
+    ("fox_arwingshot", 94),
+    ("ness_pk_flash", 95),
+    ("ness_pk_fire", 96),
+    ("ness_pk_thunder", 97),
+    ("ness_pk_starstorm", 98),
+    ("ness_paula", 99),
+    ("ness_poo", 100),
+    ("ness_yoyo", 101),
+    ("ness_yoyo_head", 102),
+    ("peach_kassar", 103),
+    ("peach_kinopio", 104),
+    ("peach_kinopiospore", 105),
+    ("daisy_kassar", 106),
+    ("daisy_kinopio", 107),
+    ("daisy_kinopiospore", 108),
+    ("koopa_breath", 109),
+    ("koopa_koopag", 110),
+    ("zelda_dein", 111),
+    ("zelda_dein_s", 112),
+    ("zelda_phantom", 113),
+    ("zelda_triforce", 114),
+    ("sheik_needle", 115),
+    ("sheik_needlehave", 116),
+    ("sheik_fusin", 117),
+    ("sheik_knife", 118),
+    ("gamewatch_food", 119),
+    ("gamewatch_rescue", 120),
+    ("gamewatch_parachute", 121),
+    ("gamewatch_panel", 122),
+    ("gamewatch_oil", 123),
+    ("gamewatch_octopus", 124),
+    ("gamewatch_normal_weapon", 125),
+    ("gamewatch_breath", 126),
+    ("gamewatch_entry", 127),
+    ("gamewatch_bomb", 128),
+    ("ganon_sword", 129),
+    ("ganon_beast", 130),
+    ("ganon_ganond", 131),
+    ("falco_blaster", 132),
+    ("falco_blaster_bullet", 133),
+    ("falco_illusion", 134),
+    ("falco_arwing", 135),
+    ("falco_reticle", 136),
+    ("falco_arwingshot", 137),
+    ("wario_wariobike", 138),
+    ("wario_garlic", 139),
+    ("wario_warioman", 140),
+    ("metaknight_mantle", 141),
+    ("metaknight_bunshin", 142),
+    ("metaknight_fourwings", 143),
+    ("pit_bow", 144),
+    ("pit_bowarrow", 145),
+    ("pit_chariot", 146),
+    ("pit_horse", 147),
+    ("pit_chariotsight", 148),
+    ("szerosuit_paralyzer_bullet", 149),
+    ("szerosuit_whip", 150),
+    ("szerosuit_paralyzer", 151),
+    ("szerosuit_samusp", 152),
+    ("szerosuit_gunship", 153),
+    ("szerosuit_reticle", 154),
+    ("szerosuit_laser", 155),
+    ("szerosuit_whip2", 156),
+    ("pikmin_pikmin", 157),
+    ("pikmin_dolfin", 158),
+    ("pikmin_win1", 159),
+    ("pikmin_win2", 160),
+    ("pikmin_win3", 161),
+    ("diddy_gun", 162),
+    ("diddy_barreljet", 163),
+    ("diddy_peanuts", 164),
+    ("diddy_explosion", 165),
+    ("diddy_barreljets", 166),
+    ("diddy_bunshin", 167),
+    ("diddy_dkbarrel", 168),
+    ("diddy_lock_on_cursor", 169),
+    ("dedede_jethammer", 170),
+    ("dedede_star_missile", 171),
+    ("dedede_star", 172),
+    ("dedede_gordo", 173),
+    ("dedede_shrine", 174),
+    ("dedede_waddledee", 175),
+    ("dedede_newdededehammer", 176),
+    ("dedede_mask", 177),
+    ("dedede_missile", 178),
+    ("ike_sword", 179),
+    ("lucario_auraball", 180),
+    ("lucario_qigong", 181),
+    ("lucario_lucariom", 182),
+    ("robot_gyro", 183),
+    ("robot_gyro_holder", 184),
+    ("robot_beam", 185),
+    ("robot_final_beam", 186),
+    ("robot_hugebeam", 187),
+    ("robot_narrowbeam", 188),
+    ("robot_widebeam", 189),
+    ("robot_mainlaser", 190),
+    ("robot_hominglaser", 191),
+    ("robot_homingtarget", 192),
+    ("toonlink_boomerang", 193),
+    ("toonlink_bow", 194),
+    ("toonlink_bowarrow", 195),
+    ("toonlink_hookshot", 196),
+    ("toonlink_hookshot_hand", 197),
+    ("toonlink_fairy", 198),
+    ("toonlink_takt", 199),
+    ("toonlink_pig", 200),
+    ("wolf_blaster", 201),
+    ("wolf_blaster_bullet", 202),
+    ("wolf_illusion", 203),
+    ("wolf_wolfen", 204),
+    ("wolf_reticle", 205),
+    ("plizardon_breath", 206),
+    ("plizardon_explosion", 207),
+    ("plizardon_daimonji", 208),
+    ("sonic_homingtarget", 209),
+    ("sonic_gimmickjump", 210),
+    ("sonic_supersonic", 211),
+    ("sonic_chaosemerald", 212),
+    ("purin_cap", 213),
+    ("purin_monsterball", 214),
+    ("mariod_drcapsule", 215),
+    ("mariod_drmantle", 216),
+    ("mariod_stethoscope", 217),
+    ("mariod_huge_capsule", 218),
+    ("mariod_capsuleblock", 219),
+    ("pichu_dengekidama", 220),
+    ("pichu_dengeki", 221),
+    ("pichu_kaminari", 222),
+    ("pichu_cloud", 223),
+    ("pichu_vortex", 224),
+    ("pichu_monsterball", 225),
+    ("pichu_specialupdummy", 226),
+    ("lucina_mask", 227),
+    ("pitb_bow", 228),
+    ("pitb_bowarrow", 229),
+    ("rosetta_tico", 230),
+    ("rosetta_starpiece", 231),
+    ("rosetta_ring", 232),
+    ("rosetta_pointer", 233),
+    ("rosetta_powerstar", 234),
+    ("rosetta_meteor", 235),
+    ("wiifit_hulahoop", 236),
+    ("wiifit_sunbullet", 237),
+    ("wiifit_balanceboard", 238),
+    ("wiifit_wiibo", 239),
+    ("wiifit_towel", 240),
+    ("wiifit_silhouette", 241),
+    ("wiifit_silhouettel", 242),
+    ("littlemac_doclouis", 243),
+    ("littlemac_sweatlittlemac", 244),
+    ("littlemac_throwsweat", 245),
+    ("littlemac_championbelt", 246),
+    ("littlemac_littlemacg", 247),
+    ("murabito_flowerpot", 248),
+    ("murabito_umbrella", 249),
+    ("murabito_bowling_ball", 250),
+    ("murabito_firework", 251),
+    ("murabito_slingshot", 252),
+    ("murabito_bullet", 253),
+    ("murabito_weeds", 254),
+    ("murabito_butterflynet", 255),
+    ("murabito_balloon", 256),
+    ("murabito_clayrocket", 257),
+    ("murabito_seed", 258),
+    ("murabito_sprout", 259),
+    ("murabito_tree", 260),
+    ("murabito_stump", 261),
+    ("murabito_sprinkling_water", 262),
+    ("murabito_helmet", 263),
+    ("murabito_tomnook", 264),
+    ("murabito_tommy", 265),
+    ("murabito_timmy", 266),
+    ("murabito_house", 267),
+    ("murabito_furniture", 268),
+    ("murabito_moneybag", 269),
+    ("murabito_beetle", 270),
+    ("palutena_godwing", 271),
+    ("palutena_explosiveflame", 272),
+    ("palutena_explosiveflame_reserve", 273),
+    ("palutena_autoaimbullet", 274),
+    ("palutena_autoreticle", 275),
+    ("palutena_reflectionboard", 276),
+    ("palutena_blackhole", 277),
+    ("palutena_beam", 278),
+    ("palutena_gate", 279),
+    ("reflet_book", 280),
+    ("reflet_window", 281),
+    ("reflet_thunder", 282),
+    ("reflet_elwind", 283),
+    ("reflet_gigafire", 284),
+    ("reflet_chrom", 285),
+    ("duckhunt_gunman", 286),
+    ("duckhunt_gunmanbullet", 287),
+    ("duckhunt_clay", 288),
+    ("duckhunt_can", 289),
+    ("duckhunt_reticle", 290),
+    ("duckhunt_kurofukuhat", 291),
+    ("duckhunt_finalduck", 292),
+    ("duckhunt_finalgunman", 293),
+    ("duckhunt_finalenemy", 294),
+    ("duckhunt_finaldog", 295),
+    ("duckhunt_finalbird", 296),
+    ("duckhunt_finalgrass", 297),
+    ("duckhunt_finalcan", 298),
+    ("duckhunt_grass", 299),
+    ("koopajr_hammer", 300),
+    ("koopajr_picopicohammer", 301),
+    ("koopajr_magichand", 302),
+    ("koopajr_cannonball", 303),
+    ("koopajr_kart", 304),
+    ("koopajr_remainclown", 305),
+    ("koopajr_shadowmario", 306),
+    ("koopajr_batten", 307),
+    ("shulk_dunban", 308),
+    ("shulk_riki", 309),
+    ("shulk_fiora", 310),
+    ("gekkouga_shuriken", 311),
+    ("gekkouga_water", 312),
+    ("gekkouga_tatami", 313),
+    ("gekkouga_moon", 314),
+    ("gekkouga_monsterball", 315),
+    ("gekkouga_bunshin", 316),
+    ("gekkouga_gekkougas", 317),
+    ("pacman_esa", 318),
+    ("pacman_trampoline", 319),
+    ("pacman_firehydrant", 320),
+    ("pacman_firehydrant_water", 321),
+    ("pacman_bigpacman", 322),
+    ("pacman_artisticpoint", 323),
+    ("pacman_fairy", 324),
+    ("rockman_rockbuster", 325),
+    ("rockman_crashbomb", 326),
+    ("rockman_rushcoil", 327),
+    ("rockman_leafshield", 328),
+    ("rockman_chargeshot", 329),
+    ("rockman_hardknuckle", 330),
+    ("rockman_blackhole", 331),
+    ("rockman_rockmanx", 332),
+    ("rockman_rockmandash", 333),
+    ("rockman_rockmanexe", 334),
+    ("rockman_shootingstarrockman", 335),
+    ("rockman_bruce", 336),
+    ("rockman_forte", 337),
+    ("rockman_leftarm", 338),
+    ("rockman_rightarm", 339),
+    ("rockman_airshooter", 340),
+    ("mewtwo_shadowball", 341),
+    ("mewtwo_bindball", 342),
+    ("mewtwo_mewtwom", 343),
+    ("mewtwo_search", 344),
+    ("mewtwo_psychobreak", 345),
+    ("mewtwo_escapeairdummy", 346),
+    ("ryu_hadoken", 347),
+    ("ryu_shinkuhadoken", 348),
+    ("ryu_sack", 349),
+    ("ken_hadoken", 350),
+    ("ken_shinkuhadoken", 351),
+    ("ken_shinryuken", 352),
+    ("lucas_pk_freeze", 353),
+    ("lucas_pk_fire", 354),
+    ("lucas_pk_thunder", 355),
+    ("lucas_pk_starstorm", 356),
+    ("lucas_himohebi", 357),
+    ("lucas_himohebi2", 358),
+    ("lucas_doseitable", 359),
+    ("lucas_needle", 360),
+    ("lucas_kumatora", 361),
+    ("lucas_bonnie", 362),
+    ("roy_sword", 363),
+    ("cloud_wave", 364),
+    ("bayonetta_specialn_bullet", 366),
+    ("bayonetta_wickedweavearm", 367),
+    ("bayonetta_wickedweaveleg", 368),
+    ("bayonetta_bat", 369),
+    ("bayonetta_gomorrah", 370),
+    ("bayonetta_hair", 371),
+    ("kamui_ryusensya", 372),
+    ("kamui_dragonhand", 373),
+    ("kamui_spearhand", 374),
+    ("kamui_waterdragon", 375),
+    ("kamui_waterstream", 376),
+    ("popo_condor", 377),
+    ("popo_blizzard", 378),
+    ("popo_rubber", 379),
+    ("popo_iceshot", 380),
+    ("popo_iceberg", 381),
+    ("popo_iceberg_wind", 382),
+    ("popo_iceberg_hit", 383),
+    ("popo_whitebear", 384),
+    ("pzenigame_water", 385),
+    ("pfushigisou_seed", 386),
+    ("pfushigisou_leafcutter", 387),
+    ("pfushigisou_vine", 388),
+    ("inkling_inkbullet", 389),
+    ("inkling_splashbomb", 390),
+    ("inkling_squid", 391),
+    ("inkling_roller", 392),
+    ("inkling_rollerink", 393),
+    ("inkling_brush", 394),
+    ("inkling_blaster", 395),
+    ("inkling_slosher", 396),
+    ("inkling_splash", 397),
+    ("inkling_megaphonelaser", 398),
+    ("inkling_copy_inklinggun", 399),
+    ("inkling_copy_inklingtank", 400),
+    ("snake_rpg7", 401),
+    ("snake_trenchmortar", 402),
+    ("snake_trenchmortar_bullet", 403),
+    ("snake_nikita", 404),
+    ("snake_nikita_missile", 405),
+    ("snake_cypher", 406),
+    ("snake_c4", 407),
+    ("snake_c4_switch", 408),
+    ("snake_grenade", 409),
+    ("snake_flare_grenades", 410),
+    ("snake_reticle", 411),
+    ("snake_reticle_cursor", 412),
+    ("snake_lock_on_cursor", 413),
+    ("snake_lock_on_cursor_ready", 414),
+    ("snake_missile", 415),
+    ("krool_backpack", 416),
+    ("krool_crown", 417),
+    ("krool_piratehat", 418),
+    ("krool_blunderbuss", 419),
+    ("krool_ironball", 420),
+    ("krool_spitball", 421),
+    ("younglink_boomerang", 422),
+    ("younglink_bow", 423),
+    ("younglink_bowarrow", 424),
+    ("younglink_hookshot", 425),
+    ("younglink_hookshot_hand", 426),
+    ("younglink_navy", 427),
+    ("younglink_milk", 428),
+    ("simon_whip", 429),
+    ("simon_axe", 430),
+    ("simon_cross", 431),
+    ("simon_coffin", 432),
+    ("simon_crystal", 433),
+    ("simon_whip2", 434),
+    ("simon_whiphand", 435),
+    ("simon_whipwire", 436),
+    ("simon_stake", 437),
+    ("chrom_sword", 438),
+    ("ridley_breath", 439),
+    ("ridley_gunship", 440),
+    ("koopag_breath", 441),
+    ("gaogaen_rope", 442),
+    ("gaogaen_championbelt", 443),
+    ("gaogaen_monsterball", 444),
+    ("gaogaen_rope2", 445),
+    ("richter_whip", 446),
+    ("richter_axe", 447),
+    ("richter_cross", 448),
+    ("richter_coffin", 449),
+    ("richter_crystal", 450),
+    ("richter_whip2", 451),
+    ("richter_whiphand", 452),
+    ("richter_whipwire", 453),
+    ("richter_stake", 454),
+    ("enemy_yellowdevil_beam", 455),
+    ("miienemyg_attackairf_bullet", 456),
+    ("miienemyg_rapidshot_bullet", 457),
+    ("ptrainer_ptrainer", 458),
+    ("ptrainer_mball", 459),
+    ("ptrainer_pzenigame", 460),
+    ("ptrainer_pfushigisou", 461),
+    ("ptrainer_plizardon", 462),
+    ("element_changer", 463),
+    ("element_diver", 464),
+    ("shizue_pot", 465),
+    ("shizue_umbrella", 466),
+    ("shizue_weeds", 467),
+    ("shizue_trafficsign", 468),
+    ("shizue_slingshot", 469),
+    ("shizue_bullet", 470),
+    ("shizue_balloon", 471),
+    ("shizue_butterflynet", 472),
+    ("shizue_office", 473),
+    ("shizue_tomnook", 474),
+    ("shizue_tommy", 475),
+    ("shizue_timmy", 476),
+    ("shizue_furniture", 477),
+    ("shizue_moneybag", 478),
+    ("shizue_picopicohammer", 479),
+    ("shizue_broom", 480),
+    ("shizue_cracker", 481),
+    ("shizue_bucket", 482),
+    ("shizue_pompon", 483),
+    ("shizue_swing", 484),
+    ("shizue_clayrocket", 485),
+    ("shizue_fishingrod", 486),
+    ("shizue_fishingline", 487),
+    ("packun_spikeball", 488),
+    ("packun_poisonbreath", 489),
+    ("packun_mario", 490),
+    ("packun_bosspackun", 491),
+    ("jack_doyle", 492),
+    ("jack_fire", 493),
+    ("jack_fire2", 494),
+    ("jack_mona", 495),
+    ("jack_wing", 496),
+    ("jack_wirerope", 497),
+    ("jack_windummy", 498),
+    ("jack_bus", 499),
+    ("jack_background", 500),
+    ("brave_fireball", 501),
+    ("brave_spark", 502),
+    ("brave_lightning", 503),
+    ("brave_tornado", 504),
+    ("brave_explosion", 505),
+    ("brave_flash", 506),
+    ("brave_deathball", 507),
+    ("brave_crash", 508),
+    ("brave_sleep", 509),
+    ("brave_blue", 510),
+    ("buddy_pad", 511),
+    ("buddy_partner", 512),
+    ("buddy_bird", 513),
+    ("buddy_bigbird", 514),
+    ("buddy_bigbirdbase", 515),
+    ("buddy_bullet", 516),
+    ("buddy_piece", 517),
+    ("buddy_strings", 518),
+    ("buddy_horn", 519),
+    ("dolly_wave", 520),
+    ("dolly_burst", 521),
+    ("dolly_cap", 522),
+    ("dolly_fire", 523),
+    ("master_axe", 524),
+    ("master_bow", 525),
+    ("master_arrow1", 526),
+    ("master_arrow2", 527),
+    ("master_spear", 528),
+    ("master_sword", 529),
+    ("master_baton", 530),
+    ("master_background", 531),
+    ("master_sword2", 532),
+    ("master_swordflare", 533),
+    ("tantan_spiralleft", 534),
+    ("tantan_spiralright", 535),
+    ("tantan_punch1", 536),
+    ("tantan_punch2", 537),
+    ("tantan_punch3", 538),
+    ("tantan_ring", 539),
+    ("tantan_beam", 540),
+    ("tantan_ally1", 541),
+    ("tantan_ally2", 542),
+    ("tantan_ally3", 543),
+    ("tantan_ally4", 544),
+    ("tantan_ally5", 545),
+    ("tantan_ally6", 546),
+    ("tantan_allyarm1", 547),
+    ("tantan_allyarm2", 548),
+    ("tantan_allyarm3", 549),
+    ("tantan_allyarm5", 550),
+    ("tantan_allyarm6", 551),
+    ("tantan_allyarmbullet1", 552),
+    ("tantan_allyarmbullet2", 553),
+    ("tantan_allyarmbullet3", 554),
+    ("tantan_spiralsimple", 555),
+    ("tantan_gongfinal", 556),
+    ("tantan_spiralleftloupe", 557),
+    ("tantan_spiralrightloupe", 558),
+    ("pickel_wing", 559),
+    ("pickel_crack", 560),
+    ("pickel_trolley", 561),
+    ("pickel_rail", 562),
+    ("pickel_plate", 563),
+    ("pickel_stone", 564),
+    ("pickel_table", 565),
+    ("pickel_stuff", 566),
+    ("pickel_building", 567),
+    ("pickel_forge", 568),
+    ("pickel_scarier", 569),
+    ("pickel_sword", 570),
+    ("pickel_axe", 571),
+    ("pickel_pick", 572),
+    ("pickel_shovel", 573),
+    ("pickel_pushobject", 574),
+    ("pickel_entryobject", 575),
+    ("pickel_fishingrod", 576),
+    ("pickel_melt", 577),
+    ("pickel_fence", 578),
+    ("pickel_fire", 579),
+    ("pickel_maskfinal", 580),
+    ("pickel_pushfinal", 581),
+    ("edge_fire", 582),
+    ("edge_flash", 583),
+    ("edge_flare1", 584),
+    ("edge_flare2", 585),
+    ("edge_flaredummy", 586),
+    ("edge_background", 587),
+    ("eflame_esword", 588),
+    ("eflame_firepillar", 589),
+    ("eflame_blazepillar", 590),
+    ("eflame_windummy", 591),
+    ("eflame_winsword", 592),
+    ("elight_esword", 593),
+    ("elight_bunshin", 594),
+    ("elight_exprosiveshot", 595),
+    ("elight_spreadbullet", 596),
+    ("elight_meteor", 597),
+    ("elight_beam", 598),
+    ("elight_windummy", 599),
+    ("elight_winsword", 600),
+    ("demon_blaster", 601),
+    ("demon_demonp", 602),
+    ("demon_blasterchest", 603),
+    ("demon_blasterhead", 604),
+    ("demon_blasterwing", 605),
+];
+
+pub struct AgentTable {
+    prefix: &'static str,
+    kinds: Vec<(&'static str, i32)>,
+    by_name: HashMap<&'static str, usize>,
+    by_id: HashMap<i32, usize>,
+    hashes: Vec<u64>,
+    owners: HashMap<i32, i32>,
+}
+
+impl AgentTable {
+    fn new(prefix: &'static str, kinds: &'static [(&'static str, i32)]) -> Self {
+        let mut table = Self {
+            prefix,
+            kinds: Vec::with_capacity(kinds.len()),
+            by_name: HashMap::with_capacity(kinds.len()),
+            by_id: HashMap::with_capacity(kinds.len()),
+            hashes: Vec::with_capacity(kinds.len()),
+            owners: HashMap::new(),
+        };
+
+        for &(name, id) in kinds {
+            table.insert(name, id);
+        }
+        table
+    }
+
+    // Adds a kind, replacing any existing entry with the same name or id. If
+    // the name and the id belong to two different entries, both are replaced.
+    fn insert(&mut self, name: &'static str, id: i32) {
+        let mut colliding: Vec<usize> = [self.by_name.get(name).copied(), self.by_id.get(&id).copied()]
+            .into_iter()
+            .flatten()
+            .collect();
+        colliding.sort_unstable();
+        colliding.dedup();
+
+        for &i in &colliding {
+            let (old_name, old_id) = self.kinds[i];
+            self.by_name.remove(old_name);
+            self.by_id.remove(&old_id);
+            self.owners.remove(&old_id);
+        }
+
+        // Removing the higher index first leaves the lower one in place.
+        if let [_, j] = colliding[..] {
+            self.kinds.swap_remove(j);
+            self.hashes.swap_remove(j);
+
+            if let Some(&(moved_name, moved_id)) = self.kinds.get(j) {
+                self.by_name.insert(moved_name, j);
+                self.by_id.insert(moved_id, j);
+            }
+        }
+
+        let hash = hash40(&(self.prefix.to_owned() + name));
+        let i = match colliding.first() {
+            Some(&i) => {
+                self.kinds[i] = (name, id);
+                self.hashes[i] = hash;
+                i
+            }
+            None => {
+                self.kinds.push((name, id));
+                self.hashes.push(hash);
+                self.kinds.len() - 1
+            }
+        };
+        self.by_name.insert(name, i);
+        self.by_id.insert(id, i);
+    }
+
+    pub fn id(&self, name: &str) -> Option<i32> {
+        self.by_name.get(name).map(|&i| self.kinds[i].1)
+    }
+
+    pub fn name(&self, id: i32) -> Option<&'static str> {
+        self.by_id.get(&id).map(|&i| self.kinds[i].0)
     }
+
+    /// `hash40` of the kind's name with its `fighter_kind_`/`weapon_kind_` prefix.
+    pub fn hash(&self, id: i32) -> Option<u64> {
+        self.by_id.get(&id).map(|&i| self.hashes[i])
+    }
+
+    pub fn id_from_hash(&self, hash: u64) -> Option<i32> {
+        self.hashes.iter().position(|&h| h == hash).map(|i| self.kinds[i].1)
+    }
+
+    pub fn prefix(&self) -> &'static str {
+        self.prefix
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, i32)> + '_ {
+        self.kinds.iter().copied()
+    }
+}
+
+pub static FIGHTERS: Lazy<RwLock<AgentTable>> = Lazy::new(|| RwLock::new(AgentTable::new("fighter_kind_", FIGHTER_KINDS)));
+pub static WEAPONS: Lazy<RwLock<AgentTable>> = Lazy::new(|| {
+    let mut weapons = AgentTable::new("weapon_kind_", WEAPON_KINDS);
+    let fighters = FIGHTERS.read();
+
+    for (weapon, weapon_id) in WEAPON_KINDS.iter().copied() {
+        if let Some(owner_id) = infer_owner(&fighters, weapon) {
+            weapons.owners.insert(weapon_id, owner_id);
+        }
+    }
+    drop(fighters);
+
+    RwLock::new(weapons)
+});
+
+// Weapons are named `<owner>_<article>`, so the owner is the fighter whose
+// name is the longest prefix of the weapon's name.
+fn infer_owner(fighters: &AgentTable, weapon: &str) -> Option<i32> {
+    fighters
+        .iter()
+        .filter(|(fighter, _)| weapon.starts_with(fighter) && weapon[fighter.len()..].starts_with('_'))
+        .max_by_key(|(fighter, _)| fighter.len())
+        .map(|(_, fighter_id)| fighter_id)
+}
+
+pub fn get_fighter_id(agent_name: &str) -> i32 {
+    FIGHTERS.read().id(agent_name).unwrap_or(-1)
+}
+
+pub fn get_fighter_name(fighter_id: i32) -> Option<&'static str> {
+    FIGHTERS.read().name(fighter_id)
+}
+
+pub fn get_fighter_hash(fighter_id: i32) -> Option<u64> {
+    FIGHTERS.read().hash(fighter_id)
+}
+
+pub fn get_fighter_id_from_hash(hash: u64) -> Option<i32> {
+    FIGHTERS.read().id_from_hash(hash)
 }
 
 pub fn get_weapon_id(agent_name: &str) -> i32 {
-    match agent_name {
-        "miifighter_hat" => 0,
-        "miifighter_ironball" => 1,
-        "miiswordsman_hat" => 2,
-        "miiswordsman_lightshuriken" => 3,
-        "miiswordsman_chakram" => 4,
-        "miiswordsman_tornadoshot" => 5,
-        "miiswordsman_wave" => 6,
-        "miigunner_hat" => 7,
-        "miigunner_rapidshot_bullet" => 8,
-        "miigunner_gunnercharge" => 9,
-        "miigunner_miimissile" => 10,
-        "miigunner_supermissile" => 11,
-        "miigunner_grenadelauncher" => 12,
-        "miigunner_flamepillar" => 13,
-        "miigunner_stealthbomb" => 14,
-        "miigunner_stealthbomb_s" => 15,
-        "miigunner_bottomshoot" => 16,
-        "miigunner_groundbomb" => 17,
-        "miigunner_attackairf_bullet" => 18,
-        "miigunner_laser" => 19,
-        "miigunner_fullthrottle" => 20,
-        "mario_fireball" => 21,
-        "mario_mantle" => 22,
-        "mario_pump" => 23,
-        "mario_pump_water" => 24,
-        "mario_huge_flame" => 25,
-        "mario_dokan" => 26,
-        "mario_cappy" => 27,
-        "donkey_dkbarrel" => 28,
-        "link_boomerang" => 29,
-        "link_bow" => 30,
-        "link_bowarrow" => 31,
-        "link_navy" => 32,
-        "link_sword_beam" => 33,
-        "link_ancient_bow" => 34,
-        "link_ancient_bowarrow" => 35,
-        "link_parasail" => 36,
-        "samus_cshot" => 37,
-        "samus_bomb" => 38,
-        "samus_missile" => 39,
-        "samus_laser" => 40,
-        "samus_laser2" => 41,
-        "samus_gun" => 42,
-        "samus_supermissile" => 43,
-        "samus_transportation" => 44,
-        "samus_gbeam" => 45,
-        "samusd_cshot" => 46,
-        "samusd_bomb" => 47,
-        "samusd_missile" => 48,
-        "samusd_laser" => 49,
-        "samusd_laser2" => 50,
-        "samusd_gun" => 51,
-        "samusd_supermissile" => 52,
-        "samusd_transportation" => 53,
-        "samusd_gbeam" => 54,
-        "samusd_bunshin" => 55,
-        "yoshi_star" => 56,
-        "yoshi_tamago" => 57,
-        "yoshi_yoshimob" => 58,
-        "yoshi_yoshibg01" => 59,
-        "kirby_hammer" => 60,
-        "kirby_finalcuttershot" => 61,
-        "kirby_starmissile" => 62,
-        "kirby_hat" => 63,
-        "kirby_ultrasword" => 64,
-        "kirby_ultraswordhat" => 65,
-        "kirby_warpstar" => 66,
-        "kirby_reserve" => 67,
-        "kirby_simple" => 68,
-        "kirby_miipartshead" => 69,
-        "kirby_rosettaticomissile" => 70,
-        "kirby_stone" => 71,
-        "kirby_simple2r" => 72,
-        "kirby_simple2l" => 73,
-        "kirby_windummy" => 74,
-        "kirby_finalcutter" => 75,
-        "pikachu_dengekidama" => 76,
-        "pikachu_dengeki" => 77,
-        "pikachu_kaminari" => 78,
-        "pikachu_cloud" => 79,
-        "pikachu_vortex" => 80,
-        "pikachu_monsterball" => 81,
-        "pikachu_specialupdummy" => 82,
-        "luigi_fireball" => 83,
-        "luigi_dokan" => 84,
-        "luigi_obakyumu" => 85,
-        "luigi_plunger" => 86,
-        "captain_bluefalcon" => 87,
-        "captain_falconpunch" => 88,
-        "fox_blaster" => 89,
-        "fox_blaster_bullet" => 90,
-        "fox_illusion" => 91,
-        "fox_arwing" => 92,
-        "fox_reticle" => 93,
-        "fox_arwingshot" => 94,
-        "ness_pk_flash" => 95,
-        "ness_pk_fire" => 96,
-        "ness_pk_thunder" => 97,
-        "ness_pk_starstorm" => 98,
-        "ness_paula" => 99,
-        "ness_poo" => 100,
-        "ness_yoyo" => 101,
-        "ness_yoyo_head" => 102,
-        "peach_kassar" => 103,
-        "peach_kinopio" => 104,
-        "peach_kinopiospore" => 105,
-        "daisy_kassar" => 106,
-        "daisy_kinopio" => 107,
-        "daisy_kinopiospore" => 108,
-        "koopa_breath" => 109,
-        "koopa_koopag" => 110,
-        "zelda_dein" => 111,
-        "zelda_dein_s" => 112,
-        "zelda_phantom" => 113,
-        "zelda_triforce" => 114,
-        "sheik_needle" => 115,
-        "sheik_needlehave" => 116,
-        "sheik_fusin" => 117,
-        "sheik_knife" => 118,
-        "gamewatch_food" => 119,
-        "gamewatch_rescue" => 120,
-        "gamewatch_parachute" => 121,
-        "gamewatch_panel" => 122,
-        "gamewatch_oil" => 123,
-        "gamewatch_octopus" => 124,
-        "gamewatch_normal_weapon" => 125,
-        "gamewatch_breath" => 126,
-        "gamewatch_entry" => 127,
-        "gamewatch_bomb" => 128,
-        "ganon_sword" => 129,
-        "ganon_beast" => 130,
-        "ganon_ganond" => 131,
-        "falco_blaster" => 132,
-        "falco_blaster_bullet" => 133,
-        "falco_illusion" => 134,
-        "falco_arwing" => 135,
-        "falco_reticle" => 136,
-        "falco_arwingshot" => 137,
-        "wario_wariobike" => 138,
-        "wario_garlic" => 139,
-        "wario_warioman" => 140,
-        "metaknight_mantle" => 141,
-        "metaknight_bunshin" => 142,
-        "metaknight_fourwings" => 143,
-        "pit_bow" => 144,
-        "pit_bowarrow" => 145,
-        "pit_chariot" => 146,
-        "pit_horse" => 147,
-        "pit_chariotsight" => 148,
-        "szerosuit_paralyzer_bullet" => 149,
-        "szerosuit_whip" => 150,
-        "szerosuit_paralyzer" => 151,
-        "szerosuit_samusp" => 152,
-        "szerosuit_gunship" => 153,
-        "szerosuit_reticle" => 154,
-        "szerosuit_laser" => 155,
-        "szerosuit_whip2" => 156,
-        "pikmin_pikmin" => 157,
-        "pikmin_dolfin" => 158,
-        "pikmin_win1" => 159,
-        "pikmin_win2" => 160,
-        "pikmin_win3" => 161,
-        "diddy_gun" => 162,
-        "diddy_barreljet" => 163,
-        "diddy_peanuts" => 164,
-        "diddy_explosion" => 165,
-        "diddy_barreljets" => 166,
-        "diddy_bunshin" => 167,
-        "diddy_dkbarrel" => 168,
-        "diddy_lock_on_cursor" => 169,
-        "dedede_jethammer" => 170,
-        "dedede_star_missile" => 171,
-        "dedede_star" => 172,
-        "dedede_gordo" => 173,
-        "dedede_shrine" => 174,
-        "dedede_waddledee" => 175,
-        "dedede_newdededehammer" => 176,
-        "dedede_mask" => 177,
-        "dedede_missile" => 178,
-        "ike_sword" => 179,
-        "lucario_auraball" => 180,
-        "lucario_qigong" => 181,
-        "lucario_lucariom" => 182,
-        "robot_gyro" => 183,
-        "robot_gyro_holder" => 184,
-        "robot_beam" => 185,
-        "robot_final_beam" => 186,
-        "robot_hugebeam" => 187,
-        "robot_narrowbeam" => 188,
-        "robot_widebeam" => 189,
-        "robot_mainlaser" => 190,
-        "robot_hominglaser" => 191,
-        "robot_homingtarget" => 192,
-        "toonlink_boomerang" => 193,
-        "toonlink_bow" => 194,
-        "toonlink_bowarrow" => 195,
-        "toonlink_hookshot" => 196,
-        "toonlink_hookshot_hand" => 197,
-        "toonlink_fairy" => 198,
-        "toonlink_takt" => 199,
-        "toonlink_pig" => 200,
-        "wolf_blaster" => 201,
-        "wolf_blaster_bullet" => 202,
-        "wolf_illusion" => 203,
-        "wolf_wolfen" => 204,
-        "wolf_reticle" => 205,
-        "plizardon_breath" => 206,
-        "plizardon_explosion" => 207,
-        "plizardon_daimonji" => 208,
-        "sonic_homingtarget" => 209,
-        "sonic_gimmickjump" => 210,
-        "sonic_supersonic" => 211,
-        "sonic_chaosemerald" => 212,
-        "purin_cap" => 213,
-        "purin_monsterball" => 214,
-        "mariod_drcapsule" => 215,
-        "mariod_drmantle" => 216,
-        "mariod_stethoscope" => 217,
-        "mariod_huge_capsule" => 218,
-        "mariod_capsuleblock" => 219,
-        "pichu_dengekidama" => 220,
-        "pichu_dengeki" => 221,
-        "pichu_kaminari" => 222,
-        "pichu_cloud" => 223,
-        "pichu_vortex" => 224,
-        "pichu_monsterball" => 225,
-        "pichu_specialupdummy" => 226,
-        "lucina_mask" => 227,
-        "pitb_bow" => 228,
-        "pitb_bowarrow" => 229,
-        "rosetta_tico" => 230,
-        "rosetta_starpiece" => 231,
-        "rosetta_ring" => 232,
-        "rosetta_pointer" => 233,
-        "rosetta_powerstar" => 234,
-        "rosetta_meteor" => 235,
-        "wiifit_hulahoop" => 236,
-        "wiifit_sunbullet" => 237,
-        "wiifit_balanceboard" => 238,
-        "wiifit_wiibo" => 239,
-        "wiifit_towel" => 240,
-        "wiifit_silhouette" => 241,
-        "wiifit_silhouettel" => 242,
-        "littlemac_doclouis" => 243,
-        "littlemac_sweatlittlemac" => 244,
-        "littlemac_throwsweat" => 245,
-        "littlemac_championbelt" => 246,
-        "littlemac_littlemacg" => 247,
-        "murabito_flowerpot" => 248,
-        "murabito_umbrella" => 249,
-        "murabito_bowling_ball" => 250,
-        "murabito_firework" => 251,
-        "murabito_slingshot" => 252,
-        "murabito_bullet" => 253,
-        "murabito_weeds" => 254,
-        "murabito_butterflynet" => 255,
-        "murabito_balloon" => 256,
-        "murabito_clayrocket" => 257,
-        "murabito_seed" => 258,
-        "murabito_sprout" => 259,
-        "murabito_tree" => 260,
-        "murabito_stump" => 261,
-        "murabito_sprinkling_water" => 262,
-        "murabito_helmet" => 263,
-        "murabito_tomnook" => 264,
-        "murabito_tommy" => 265,
-        "murabito_timmy" => 266,
-        "murabito_house" => 267,
-        "murabito_furniture" => 268,
-        "murabito_moneybag" => 269,
-        "murabito_beetle" => 270,
-        "palutena_godwing" => 271,
-        "palutena_explosiveflame" => 272,
-        "palutena_explosiveflame_reserve" => 273,
-        "palutena_autoaimbullet" => 274,
-        "palutena_autoreticle" => 275,
-        "palutena_reflectionboard" => 276,
-        "palutena_blackhole" => 277,
-        "palutena_beam" => 278,
-        "palutena_gate" => 279,
-        "reflet_book" => 280,
-        "reflet_window" => 281,
-        "reflet_thunder" => 282,
-        "reflet_elwind" => 283,
-        "reflet_gigafire" => 284,
-        "reflet_chrom" => 285,
-        "duckhunt_gunman" => 286,
-        "duckhunt_gunmanbullet" => 287,
-        "duckhunt_clay" => 288,
-        "duckhunt_can" => 289,
-        "duckhunt_reticle" => 290,
-        "duckhunt_kurofukuhat" => 291,
-        "duckhunt_finalduck" => 292,
-        "duckhunt_finalgunman" => 293,
-        "duckhunt_finalenemy" => 294,
-        "duckhunt_finaldog" => 295,
-        "duckhunt_finalbird" => 296,
-        "duckhunt_finalgrass" => 297,
-        "duckhunt_finalcan" => 298,
-        "duckhunt_grass" => 299,
-        "koopajr_hammer" => 300,
-        "koopajr_picopicohammer" => 301,
-        "koopajr_magichand" => 302,
-        "koopajr_cannonball" => 303,
-        "koopajr_kart" => 304,
-        "koopajr_remainclown" => 305,
-        "koopajr_shadowmario" => 306,
-        "koopajr_batten" => 307,
-        "shulk_dunban" => 308,
-        "shulk_riki" => 309,
-        "shulk_fiora" => 310,
-        "gekkouga_shuriken" => 311,
-        "gekkouga_water" => 312,
-        "gekkouga_tatami" => 313,
-        "gekkouga_moon" => 314,
-        "gekkouga_monsterball" => 315,
-        "gekkouga_bunshin" => 316,
-        "gekkouga_gekkougas" => 317,
-        "pacman_esa" => 318,
-        "pacman_trampoline" => 319,
-        "pacman_firehydrant" => 320,
-        "pacman_firehydrant_water" => 321,
-        "pacman_bigpacman" => 322,
-        "pacman_artisticpoint" => 323,
-        "pacman_fairy" => 324,
-        "rockman_rockbuster" => 325,
-        "rockman_crashbomb" => 326,
-        "rockman_rushcoil" => 327,
-        "rockman_leafshield" => 328,
-        "rockman_chargeshot" => 329,
-        "rockman_hardknuckle" => 330,
-        "rockman_blackhole" => 331,
-        "rockman_rockmanx" => 332,
-        "rockman_rockmandash" => 333,
-        "rockman_rockmanexe" => 334,
-        "rockman_shootingstarrockman" => 335,
-        "rockman_bruce" => 336,
-        "rockman_forte" => 337,
-        "rockman_leftarm" => 338,
-        "rockman_rightarm" => 339,
-        "rockman_airshooter" => 340,
-        "mewtwo_shadowball" => 341,
-        "mewtwo_bindball" => 342,
-        "mewtwo_mewtwom" => 343,
-        "mewtwo_search" => 344,
-        "mewtwo_psychobreak" => 345,
-        "mewtwo_escapeairdummy" => 346,
-        "ryu_hadoken" => 347,
-        "ryu_shinkuhadoken" => 348,
-        "ryu_sack" => 349,
-        "ken_hadoken" => 350,
-        "ken_shinkuhadoken" => 351,
-        "ken_shinryuken" => 352,
-        "lucas_pk_freeze" => 353,
-        "lucas_pk_fire" => 354,
-        "lucas_pk_thunder" => 355,
-        "lucas_pk_starstorm" => 356,
-        "lucas_himohebi" => 357,
-        "lucas_himohebi2" => 358,
-        "lucas_doseitable" => 359,
-        "lucas_needle" => 360,
-        "lucas_kumatora" => 361,
-        "lucas_bonnie" => 362,
-        "roy_sword" => 363,
-        "cloud_wave" => 364,
-        "bayonetta_specialn_bullet" => 366,
-        "bayonetta_wickedweavearm" => 367,
-        "bayonetta_wickedweaveleg" => 368,
-        "bayonetta_bat" => 369,
-        "bayonetta_gomorrah" => 370,
-        "bayonetta_hair" => 371,
-        "kamui_ryusensya" => 372,
-        "kamui_dragonhand" => 373,
-        "kamui_spearhand" => 374,
-        "kamui_waterdragon" => 375,
-        "kamui_waterstream" => 376,
-        "popo_condor" => 377,
-        "popo_blizzard" => 378,
-        "popo_rubber" => 379,
-        "popo_iceshot" => 380,
-        "popo_iceberg" => 381,
-        "popo_iceberg_wind" => 382,
-        "popo_iceberg_hit" => 383,
-        "popo_whitebear" => 384,
-        "pzenigame_water" => 385,
-        "pfushigisou_seed" => 386,
-        "pfushigisou_leafcutter" => 387,
-        "pfushigisou_vine" => 388,
-        "inkling_inkbullet" => 389,
-        "inkling_splashbomb" => 390,
-        "inkling_squid" => 391,
-        "inkling_roller" => 392,
-        "inkling_rollerink" => 393,
-        "inkling_brush" => 394,
-        "inkling_blaster" => 395,
-        "inkling_slosher" => 396,
-        "inkling_splash" => 397,
-        "inkling_megaphonelaser" => 398,
-        "inkling_copy_inklinggun" => 399,
-        "inkling_copy_inklingtank" => 400,
-        "snake_rpg7" => 401,
-        "snake_trenchmortar" => 402,
-        "snake_trenchmortar_bullet" => 403,
-        "snake_nikita" => 404,
-        "snake_nikita_missile" => 405,
-        "snake_cypher" => 406,
-        "snake_c4" => 407,
-        "snake_c4_switch" => 408,
-        "snake_grenade" => 409,
-        "snake_flare_grenades" => 410,
-        "snake_reticle" => 411,
-        "snake_reticle_cursor" => 412,
-        "snake_lock_on_cursor" => 413,
-        "snake_lock_on_cursor_ready" => 414,
-        "snake_missile" => 415,
-        "krool_backpack" => 416,
-        "krool_crown" => 417,
-        "krool_piratehat" => 418,
-        "krool_blunderbuss" => 419,
-        "krool_ironball" => 420,
-        "krool_spitball" => 421,
-        "younglink_boomerang" => 422,
-        "younglink_bow" => 423,
-        "younglink_bowarrow" => 424,
-        "younglink_hookshot" => 425,
-        "younglink_hookshot_hand" => 426,
-        "younglink_navy" => 427,
-        "younglink_milk" => 428,
-        "simon_whip" => 429,
-        "simon_axe" => 430,
-        "simon_cross" => 431,
-        "simon_coffin" => 432,
-        "simon_crystal" => 433,
-        "simon_whip2" => 434,
-        "simon_whiphand" => 435,
-        "simon_whipwire" => 436,
-        "simon_stake" => 437,
-        "chrom_sword" => 438,
-        "ridley_breath" => 439,
-        "ridley_gunship" => 440,
-        "koopag_breath" => 441,
-        "gaogaen_rope" => 442,
-        "gaogaen_championbelt" => 443,
-        "gaogaen_monsterball" => 444,
-        "gaogaen_rope2" => 445,
-        "richter_whip" => 446,
-        "richter_axe" => 447,
-        "richter_cross" => 448,
-        "richter_coffin" => 449,
-        "richter_crystal" => 450,
-        "richter_whip2" => 451,
-        "richter_whiphand" => 452,
-        "richter_whipwire" => 453,
-        "richter_stake" => 454,
-        "enemy_yellowdevil_beam" => 455,
-        "miienemyg_attackairf_bullet" => 456,
-        "miienemyg_rapidshot_bullet" => 457,
-        "ptrainer_ptrainer" => 458,
-        "ptrainer_mball" => 459,
-        "ptrainer_pzenigame" => 460,
-        "ptrainer_pfushigisou" => 461,
-        "ptrainer_plizardon" => 462,
-        "element_changer" => 463,
-        "element_diver" => 464,
-        "shizue_pot" => 465,
-        "shizue_umbrella" => 466,
-        "shizue_weeds" => 467,
-        "shizue_trafficsign" => 468,
-        "shizue_slingshot" => 469,
-        "shizue_bullet" => 470,
-        "shizue_balloon" => 471,
-        "shizue_butterflynet" => 472,
-        "shizue_office" => 473,
-        "shizue_tomnook" => 474,
-        "shizue_tommy" => 475,
-        "shizue_timmy" => 476,
-        "shizue_furniture" => 477,
-        "shizue_moneybag" => 478,
-        "shizue_picopicohammer" => 479,
-        "shizue_broom" => 480,
-        "shizue_cracker" => 481,
-        "shizue_bucket" => 482,
-        "shizue_pompon" => 483,
-        "shizue_swing" => 484,
-        "shizue_clayrocket" => 485,
-        "shizue_fishingrod" => 486,
-        "shizue_fishingline" => 487,
-        "packun_spikeball" => 488,
-        "packun_poisonbreath" => 489,
-        "packun_mario" => 490,
-        "packun_bosspackun" => 491,
-        "jack_doyle" => 492,
-        "jack_fire" => 493,
-        "jack_fire2" => 494,
-        "jack_mona" => 495,
-        "jack_wing" => 496,
-        "jack_wirerope" => 497,
-        "jack_windummy" => 498,
-        "jack_bus" => 499,
-        "jack_background" => 500,
-        "brave_fireball" => 501,
-        "brave_spark" => 502,
-        "brave_lightning" => 503,
-        "brave_tornado" => 504,
-        "brave_explosion" => 505,
-        "brave_flash" => 506,
-        "brave_deathball" => 507,
-        "brave_crash" => 508,
-        "brave_sleep" => 509,
-        "brave_blue" => 510,
-        "buddy_pad" => 511,
-        "buddy_partner" => 512,
-        "buddy_bird" => 513,
-        "buddy_bigbird" => 514,
-        "buddy_bigbirdbase" => 515,
-        "buddy_bullet" => 516,
-        "buddy_piece" => 517,
-        "buddy_strings" => 518,
-        "buddy_horn" => 519,
-        "dolly_wave" => 520,
-        "dolly_burst" => 521,
-        "dolly_cap" => 522,
-        "dolly_fire" => 523,
-        "master_axe" => 524,
-        "master_bow" => 525,
-        "master_arrow1" => 526,
-        "master_arrow2" => 527,
-        "master_spear" => 528,
-        "master_sword" => 529,
-        "master_baton" => 530,
-        "master_background" => 531,
-        "master_sword2" => 532,
-        "master_swordflare" => 533,
-        "tantan_spiralleft" => 534,
-        "tantan_spiralright" => 535,
-        "tantan_punch1" => 536,
-        "tantan_punch2" => 537,
-        "tantan_punch3" => 538,
-        "tantan_ring" => 539,
-        "tantan_beam" => 540,
-        "tantan_ally1" => 541,
-        "tantan_ally2" => 542,
-        "tantan_ally3" => 543,
-        "tantan_ally4" => 544,
-        "tantan_ally5" => 545,
-        "tantan_ally6" => 546,
-        "tantan_allyarm1" => 547,
-        "tantan_allyarm2" => 548,
-        "tantan_allyarm3" => 549,
-        "tantan_allyarm5" => 550,
-        "tantan_allyarm6" => 551,
-        "tantan_allyarmbullet1" => 552,
-        "tantan_allyarmbullet2" => 553,
-        "tantan_allyarmbullet3" => 554,
-        "tantan_spiralsimple" => 555,
-        "tantan_gongfinal" => 556,
-        "tantan_spiralleftloupe" => 557,
-        "tantan_spiralrightloupe" => 558,
-        "pickel_wing" => 559,
-        "pickel_crack" => 560,
-        "pickel_trolley" => 561,
-        "pickel_rail" => 562,
-        "pickel_plate" => 563,
-        "pickel_stone" => 564,
-        "pickel_table" => 565,
-        "pickel_stuff" => 566,
-        "pickel_building" => 567,
-        "pickel_forge" => 568,
-        "pickel_scarier" => 569,
-        "pickel_sword" => 570,
-        "pickel_axe" => 571,
-        "pickel_pick" => 572,
-        "pickel_shovel" => 573,
-        "pickel_pushobject" => 574,
-        "pickel_entryobject" => 575,
-        "pickel_fishingrod" => 576,
-        "pickel_melt" => 577,
-        "pickel_fence" => 578,
-        "pickel_fire" => 579,
-        "pickel_maskfinal" => 580,
-        "pickel_pushfinal" => 581,
-        "edge_fire" => 582,
-        "edge_flash" => 583,
-        "edge_flare1" => 584,
-        "edge_flare2" => 585,
-        "edge_flaredummy" => 586,
-        "edge_background" => 587,
-        "eflame_esword" => 588,
-        "eflame_firepillar" => 589,
-        "eflame_blazepillar" => 590,
-        "eflame_windummy" => 591,
-        "eflame_winsword" => 592,
-        "elight_esword" => 593,
-        "elight_bunshin" => 594,
-        "elight_exprosiveshot" => 595,
-        "elight_spreadbullet" => 596,
-        "elight_meteor" => 597,
-        "elight_beam" => 598,
-        "elight_windummy" => 599,
-        "elight_winsword" => 600,
-        "demon_blaster" => 601,
-        "demon_demonp" => 602,
-        "demon_blasterchest" => 603,
-        "demon_blasterhead" => 604,
-        "demon_blasterwing" => 605,
-        _ => -1
+    WEAPONS.read().id(agent_name).unwrap_or(-1)
+}
+
+pub fn get_weapon_name(weapon_id: i32) -> Option<&'static str> {
+    WEAPONS.read().name(weapon_id)
+}
+
+pub fn get_weapon_hash(weapon_id: i32) -> Option<u64> {
+    WEAPONS.read().hash(weapon_id)
+}
+
+pub fn get_weapon_id_from_hash(hash: u64) -> Option<i32> {
+    WEAPONS.read().id_from_hash(hash)
+}
+
+/// Fighter kind that owns the weapon, e.g. `mario` for `mario_fireball`.
+pub fn get_weapon_owner_id(weapon_id: i32) -> Option<i32> {
+    WEAPONS.read().owners.get(&weapon_id).copied()
+}
+
+pub fn get_weapon_owner_name(weapon_id: i32) -> Option<&'static str> {
+    get_weapon_owner_id(weapon_id).and_then(get_fighter_name)
+}
+
+pub fn get_fighter_weapons(fighter_id: i32) -> Vec<(&'static str, i32)> {
+    let weapons = WEAPONS.read();
+    let fighter_weapons = weapons
+        .iter()
+        .filter(|(_, weapon_id)| weapons.owners.get(weapon_id) == Some(&fighter_id))
+        .collect();
+    fighter_weapons
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentCategory {
+    Fighter,
+    /// `owner` is the owning fighter's name. If `None`, it's inferred from the
+    /// weapon's name.
+    Weapon { owner: Option<String> },
+}
+
+/// Makes a fighter or weapon kind that isn't part of the base game, e.g. one
+/// added by a kind-extension mod, known to the crate so it can be passed to
+/// `SlottedAgent::new`. Registering an existing name or id replaces it.
+pub fn register_agent_kind(name: &str, kind: i32, category: AgentCategory) {
+    let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+
+    match category {
+        AgentCategory::Fighter => FIGHTERS.write().insert(name, kind),
+        AgentCategory::Weapon { owner } => {
+            let owner_id = {
+                let fighters = FIGHTERS.read();
+                match owner {
+                    Some(owner) => fighters.id(&owner),
+                    None => infer_owner(&fighters, name),
+                }
+            };
+
+            let mut weapons = WEAPONS.write();
+            weapons.insert(name, kind);
+            if let Some(owner_id) = owner_id {
+                weapons.owners.insert(kind, owner_id);
+            } else {
+                agent_log!(warn, name, "Couldn't find the owner of weapon `{}`", name);
+            }
+        }
+    }
+}
+
+// Fighters that share an entry and are always on screen together.
+static PARTNERS: &[(&str, &str)] = &[
+    ("popo", "nana"),
+];
+
+/// The fighter that makes up the same entry as `fighter`, e.g. `nana` for `popo`.
+pub fn get_partner_name(fighter: &str) -> Option<&'static str> {
+    PARTNERS.iter().find_map(|&(a, b)| {
+        if a == fighter {
+            Some(b)
+        } else if b == fighter {
+            Some(a)
+        } else {
+            None
+        }
+    })
+}
+
+// Fighters that take turns as a single entry and share its costume.
+static TEAMS: &[&[&str]] = &[
+    &["ptrainer", "pzenigame", "pfushigisou", "plizardon"],
+    &["element", "eflame", "elight"],
+];
+
+/// Every fighter of the team `fighter` belongs to, including itself. Empty if
+/// it isn't part of one.
+pub fn get_team(fighter: &str) -> &'static [&'static str] {
+    TEAMS
+        .iter()
+        .find(|team| team.contains(&fighter))
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Adds the fighter and weapon kinds listed in `path` on top of the built-in
+/// tables, replacing built-in entries with the same name or id. Nothing is
+/// loaded unless a plugin calls this before creating its agents.
+///
+/// The file is written by hand (or by whatever tool the mod adding the kinds
+/// provides), one kind per line:
+///
+/// ```text
+/// fighter <kind id> <name>
+/// weapon <kind id> <name> [owner fighter name]
+/// ```
+///
+/// Blank lines and lines starting with `#` are skipped. If the owner of a
+/// weapon is left out it's inferred from its name. Returns how many kinds were
+/// loaded.
+pub fn load_agent_tables(path: &str) -> std::io::Result<usize> {
+    let contents = std::fs::read_to_string(path)?;
+    let mut loaded = 0;
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (Some(&category), Some(Ok(id)), Some(&name)) = (fields.first(), fields.get(1).map(|id| id.parse::<i32>()), fields.get(2)) else {
+            log::warn!("{}:{}: couldn't parse `{}`, skipping...", path, i + 1, line);
+            continue;
+        };
+
+        let category = match category {
+            "fighter" => AgentCategory::Fighter,
+            "weapon" => AgentCategory::Weapon {
+                owner: fields.get(3).map(|owner| owner.to_string()),
+            },
+            _ => {
+                log::warn!("{}:{}: unknown agent category `{}`, skipping...", path, i + 1, category);
+                continue;
+            }
+        };
+        register_agent_kind(name, id, category);
+        loaded += 1;
     }
+
+    Ok(loaded)
 }